@@ -0,0 +1,111 @@
+use crate::*;
+use crate::markov::turns_to_finish;
+use rand::RngCore;
+
+/// A player making the choices the rules leave open
+/// Agents are shared by every game of a run, so any randomness has to come from the game's RNG
+pub trait PlayerAgent: Send + Sync {
+    /// Whether to follow an optional shortcut from space `from` to space `to`
+    fn take_shortcut(&self, board: &[Space], from: usize, to: usize, rng: &mut dyn RngCore) -> bool;
+
+    /// Whether the agent's choices draw on the RNG, so a fixed deck no longer plays out the same way from the same state
+    fn is_random(&self) -> bool {
+        false
+    }
+}
+
+/// Always takes a shortcut
+pub struct Always;
+
+/// Never takes a shortcut
+pub struct Never;
+
+/// Takes a shortcut half of the time
+pub struct Random;
+
+/// Takes a shortcut when it's expected to leave fewer turns to finish, going by a single player Markov analysis
+pub struct Greedy {
+    turns: Option<Vec<[f64; 2]>>, // Expected turns to finish from each space; None (always take it) if there's no estimate
+}
+
+impl PlayerAgent for Always {
+    fn take_shortcut(&self, _: &[Space], _: usize, _: usize, _: &mut dyn RngCore) -> bool {
+        true
+    }
+}
+
+impl PlayerAgent for Never {
+    fn take_shortcut(&self, _: &[Space], _: usize, _: usize, _: &mut dyn RngCore) -> bool {
+        false
+    }
+}
+
+impl PlayerAgent for Random {
+    fn take_shortcut(&self, _: &[Space], _: usize, _: usize, rng: &mut dyn RngCore) -> bool {
+        rng.gen_bool(0.5)
+    }
+
+    fn is_random(&self) -> bool {
+        true
+    }
+}
+
+impl Greedy {
+    pub fn new(c: &Config) -> Greedy {
+        Greedy {turns: turns_to_finish(&c.board, &c.deck_spec, &c.rules)}
+    }
+}
+
+impl PlayerAgent for Greedy {
+    fn take_shortcut(&self, board: &[Space], from: usize, to: usize, _: &mut dyn RngCore) -> bool {
+        let Some(turns) = &self.turns else {
+            return true;
+        };
+        let stuck = board[from].sticky as usize;
+        turns[to][stuck] <= turns[from][stuck]
+    }
+}
+
+/// The built-in agents, as they are picked for each seat in a `Config`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AgentKind {
+    Always,
+    Never,
+    Greedy,
+    Random,
+}
+
+impl AgentKind {
+    pub const ALL: [AgentKind; 4] = [AgentKind::Always, AgentKind::Never, AgentKind::Greedy, AgentKind::Random];
+
+    /// Set up this kind of agent for the games of the given config
+    pub fn build(&self, c: &Config) -> Box<dyn PlayerAgent> {
+        match self {
+            AgentKind::Always => Box::new(Always),
+            AgentKind::Never => Box::new(Never),
+            AgentKind::Greedy => Box::new(Greedy::new(c)),
+            AgentKind::Random => Box::new(Random),
+        }
+    }
+}
+
+impl std::fmt::Display for AgentKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AgentKind::Always => write!(f, "always"),
+            AgentKind::Never => write!(f, "never"),
+            AgentKind::Greedy => write!(f, "greedy"),
+            AgentKind::Random => write!(f, "random"),
+        }
+    }
+}
+
+impl std::str::FromStr for AgentKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<AgentKind, String> {
+        AgentKind::ALL.into_iter().find(|a| a.to_string() == s)
+            .ok_or_else(|| format!("'{}' isn't an agent (always, never, greedy or random)", s))
+    }
+}
@@ -0,0 +1,227 @@
+use crate::*;
+use std::collections::BTreeMap;
+
+/// Cards moving more spaces than this are counted together with it
+const MAX_NUM: usize = 4;
+const KINDS: usize = 13 * MAX_NUM;
+
+const TILES: [Tile; 13] = [
+    Tile::Start, Tile::Red, Tile::Orange, Tile::Yellow, Tile::Green, Tile::Blue, Tile::Purple,
+    Tile::IceCreamCone, Tile::Gumdrop, Tile::Lollipop, Tile::Peppermint, Tile::BonBon, Tile::End,
+];
+
+/// A count for each kind of card (tile and number of spaces)
+/// Serialized as a map from card names such as "Red 2" or "Peppermint" to counts, leaving out zeros
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(into = "BTreeMap<String, u64>", try_from = "BTreeMap<String, u64>")]
+pub struct CardCounts([u64; KINDS]);
+
+impl Default for CardCounts {
+    fn default() -> CardCounts {
+        CardCounts([0; KINDS])
+    }
+}
+
+impl CardCounts {
+    fn index(card: &Card) -> usize {
+        card.tile.clone() as usize * MAX_NUM + (card.num as usize).clamp(1, MAX_NUM) - 1
+    }
+
+    pub fn add(&mut self, card: &Card) {
+        self.0[CardCounts::index(card)] += 1;
+    }
+
+    pub fn get(&self, card: &Card) -> u64 {
+        self.0[CardCounts::index(card)]
+    }
+
+    pub fn total(&self) -> u64 {
+        self.0.iter().sum()
+    }
+
+    pub fn merge(&mut self, other: &CardCounts) {
+        for (c, o) in self.0.iter_mut().zip(other.0.iter()) {
+            *c += o;
+        }
+    }
+
+    /// Every kind of card with a non-zero count
+    pub fn iter(&self) -> impl Iterator<Item = (Card, u64)> + '_ {
+        self.0.iter().enumerate().filter(|(_, &n)| n > 0).map(|(i, &n)| {
+            (Card {tile: TILES[i / MAX_NUM].clone(), num: (i % MAX_NUM) as u32 + 1}, n)
+        })
+    }
+}
+
+/// Name of a kind of card, as used in reports and serialized counts
+pub fn card_name(card: &Card) -> String {
+    match card.tile {
+        _ if card.tile.is_picture() => format!("{:?}", card.tile),
+        _ if card.num as usize >= MAX_NUM => format!("{:?} {}+", card.tile, MAX_NUM),
+        _ => format!("{:?} {}", card.tile, card.num),
+    }
+}
+
+impl From<CardCounts> for BTreeMap<String, u64> {
+    fn from(counts: CardCounts) -> BTreeMap<String, u64> {
+        counts.iter().map(|(c, n)| (card_name(&c), n)).collect()
+    }
+}
+
+impl TryFrom<BTreeMap<String, u64>> for CardCounts {
+    type Error = String;
+
+    fn try_from(map: BTreeMap<String, u64>) -> Result<CardCounts, String> {
+        let mut counts = CardCounts::default();
+        for (name, n) in map {
+            let (tile, num) = match name.split_once(' ') {
+                Some((tile, num)) => (tile, num.trim_end_matches('+').parse().map_err(|_| format!("bad card name '{}'", name))?),
+                None => (name.as_str(), 1),
+            };
+            let tile = TILES.iter().find(|t| format!("{:?}", t) == tile).ok_or_else(|| format!("bad card name '{}'", name))?;
+            counts.0[CardCounts::index(&Card {tile: tile.clone(), num})] += n;
+        }
+        Ok(counts)
+    }
+}
+
+/// What the cards did over one or more games
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CardTable {
+    pub drawn: CardCounts, // Cards drawn, by kind
+    pub backward: CardCounts, // Picture cards that sent a player backwards
+    pub leader_setbacks: CardCounts, // Picture cards that sent the sole leader backwards
+    pub leader_setback_losses: CardCounts, // ...where that leader went on to lose the game
+}
+
+impl CardTable {
+    pub fn merge(&mut self, other: &CardTable) {
+        self.drawn.merge(&other.drawn);
+        self.backward.merge(&other.backward);
+        self.leader_setbacks.merge(&other.leader_setbacks);
+        self.leader_setback_losses.merge(&other.leader_setback_losses);
+    }
+}
+
+/// Print a table of how often each kind of card was drawn over the given number of games, and what it did
+pub fn print_cards(t: &CardTable, games: u64) {
+    println!("{:<14} {:>12} {:>9} {:>10} {:>10} {:>10}", "Card", "Drawn", "Per game", "Backward", "Set back", "...lost");
+    for (card, drawn) in t.drawn.iter() {
+        println!("{:<14} {:>12} {:>9.3} {:>10} {:>10} {:>10}", card_name(&card), drawn, drawn as f64 / games.max(1) as f64,
+            t.backward.get(&card), t.leader_setbacks.get(&card), t.leader_setback_losses.get(&card));
+    }
+    println!("{:<14} {:>12} {:>9.3}", "Total", t.drawn.total(), t.drawn.total() as f64 / games.max(1) as f64);
+}
+
+/// How the race went for one seat at the table, over one or more games
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SeatStats {
+    pub lead_changes: u64, // Times this seat took the lead from another player
+    pub backward_moves: u64,
+    pub spaces_lost: u64, // Total distance of all backward moves
+    pub comebacks: u64, // Total distance this seat was behind the leader, summed over the games it won
+    pub largest_comeback: u32, // Farthest this seat was ever behind the leader in a game it went on to win
+}
+
+impl SeatStats {
+    pub fn merge(&mut self, other: &SeatStats) {
+        self.lead_changes += other.lead_changes;
+        self.backward_moves += other.backward_moves;
+        self.spaces_lost += other.spaces_lost;
+        self.comebacks += other.comebacks;
+        self.largest_comeback = self.largest_comeback.max(other.largest_comeback);
+    }
+}
+
+/// Print a table of how the race went for each seat, given how many games each seat won
+pub fn print_seats(seats: &[SeatStats], winners: &[u32], games: u64) {
+    println!("{:<6} {:>10} {:>12} {:>12} {:>12} {:>14} {:>9}", "Seat", "Wins", "Took lead", "Backward", "Spaces lost", "Avg comeback", "Largest");
+    for (i, (seat, &wins)) in seats.iter().zip(winners).enumerate() {
+        println!("{:<6} {:>10} {:>12} {:>12} {:>12} {:>14.3} {:>9}", format!("#{}", i + 1), wins, seat.lead_changes,
+            seat.backward_moves, seat.spaces_lost, seat.comebacks as f64 / wins.max(1) as f64, seat.largest_comeback);
+    }
+    let lead_changes: u64 = seats.iter().map(|s| s.lead_changes).sum();
+    println!("Lead changes per game: {:.3}", lead_changes as f64 / games.max(1) as f64);
+}
+
+/// Print a seat advantage report: each seat's win share against a fair one, and whether the difference is significant
+pub fn print_seat_advantage(a: &SeatAdvantage) {
+    let fair = 1.0 / a.seats.len().max(1) as f64;
+    println!("{:<6} {:>10} {:>9} {:>19} {:>9}", "Seat", "Wins", "Share", "95% CI", "vs fair");
+    for (i, w) in a.seats.iter().enumerate() {
+        println!("{:<6} {:>10} {:>8.3}% {:>19} {:>+8.3}%", format!("#{}", i + 1), w.wins, w.rate * 100.0,
+            format!("{:.3}-{:.3}%", w.low * 100.0, w.high * 100.0), (w.rate - fair) * 100.0);
+    }
+    println!("Chi-square vs equal seats: {:.3} on {} df, p = {:.4e}", a.chi_square, a.degrees, a.p_value);
+    if let Some(n) = a.games_needed {
+        println!("Games needed to detect a {:.2} point edge for one seat: {}", a.edge * 100.0, n);
+    }
+}
+
+/// What happened on one space of the board, over one or more games
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SpaceStats {
+    pub landings: u64, // Moves that ended on this space, before any shortcut was taken
+    pub licorice_stops: u64, // Landings that got a player stuck here
+    pub shortcuts: u64, // Shortcuts taken from here
+    pub pictures: u64, // Picture cards that brought a player here
+}
+
+impl SpaceStats {
+    pub fn merge(&mut self, other: &SpaceStats) {
+        self.landings += other.landings;
+        self.licorice_stops += other.licorice_stops;
+        self.shortcuts += other.shortcuts;
+        self.pictures += other.pictures;
+    }
+}
+
+/// The per-space counts as CSV, one row per space of the board
+pub fn spaces_csv(spaces: &[SpaceStats], board: &BoardSpec, games: u64) -> String {
+    let mut csv = String::from("space,tile,landings,landings_per_game,licorice_stops,shortcuts,picture_arrivals\n");
+    for (i, (s, tile)) in spaces.iter().zip(&board.tiles).enumerate() {
+        csv += &format!("{},{:?},{},{:.6},{},{},{}\n", i, tile, s.landings, s.landings as f64 / games.max(1) as f64,
+            s.licorice_stops, s.shortcuts, s.pictures);
+    }
+    csv
+}
+
+/// Spaces per row of the heatmap
+const HEATMAP_WIDTH: usize = 12;
+
+/// Characters for increasingly busy spaces
+const HEAT: &[u8] = b" .:-=+#%@";
+
+/// Print a heatmap of landings along the path of the board, winding back and forth a row at a time
+/// Each space shows its tile, how busy it is, and `*` for licorice or `>` for a shortcut
+/// The End is left out of the scale and the hottest spaces (and drawn blank), as every finished game lands on it
+pub fn print_heatmap(spaces: &[SpaceStats], board: &BoardSpec, games: u64) {
+    let built = board.build();
+    let most = spaces.iter().zip(&built).filter(|(_, b)| b.tile != Tile::End).map(|(s, _)| s.landings).max().unwrap_or(0).max(1);
+    let cells: Vec<String> = built.iter().zip(spaces).map(|(space, s)| {
+        let name = format!("{:?}", space.tile);
+        let heat = if space.tile == Tile::End {
+            ' '
+        } else {
+            HEAT[((s.landings * (HEAT.len() as u64 - 1)).div_ceil(most) as usize).min(HEAT.len() - 1)] as char
+        };
+        let mark = if space.sticky { '*' } else if space.shortcut.is_some() { '>' } else { ' ' };
+        format!("{}{}{}", &name[..2], heat, mark)
+    }).collect();
+    for (row, chunk) in cells.chunks(HEATMAP_WIDTH).enumerate() {
+        let mut line: Vec<&str> = chunk.iter().map(|c| c.as_str()).collect();
+        if row % 2 == 1 {
+            line.reverse();
+            print!("{}", "     ".repeat(HEATMAP_WIDTH - line.len()));
+        }
+        println!("{}", line.join(" "));
+    }
+    println!("Heat: '{}' from no landings to {:.3} per game", String::from_utf8_lossy(HEAT), most as f64 / games.max(1) as f64);
+
+    let mut hottest: Vec<usize> = (0..spaces.len()).filter(|&i| built[i].tile != Tile::End).collect();
+    hottest.sort_by_key(|&i| std::cmp::Reverse(spaces[i].landings));
+    for &i in hottest.iter().take(5) {
+        let s = &spaces[i];
+        println!("#{:<3} {:<13} {:.3} landings per game", i, format!("{:?}", built[i].tile), s.landings as f64 / games.max(1) as f64);
+    }
+}
@@ -1,111 +1,315 @@
 use crate::*;
-use std::cell::Cell;
 
+#[derive(Clone, Debug)]
 pub struct Space {
-    tile: Tile,
-    shortcut: Option<usize>,
-    sticky: bool,
-    player: Cell<bool>,
+    pub tile: Tile,
+    pub shortcut: Option<usize>, // Space this one immediately sends a player to
+    pub sticky: bool, // Licorice: see `Rules::licorice`
 }
 
 pub fn get_board() -> Vec<Space> {
     vec![
-        Space {tile: Tile::Start,        shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Red,          shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Purple,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Blue,         shortcut: Some(36), sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Orange,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Green,        shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Red,          shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Purple,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Blue,         shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Orange,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Green,        shortcut: Some(18), sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Red,          shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Purple,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Blue,         shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Orange,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Green,        shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Red,          shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Purple,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Peppermint,   shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Blue,         shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Orange,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Green,        shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Red,          shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Purple,       shortcut: None,     sticky: true,  player: Cell::new(false)},
-        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Blue,         shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Orange,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Green,        shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Red,          shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Gumdrop,      shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Purple,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Blue,         shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Orange,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Green,        shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Red,          shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Purple,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Blue,         shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Orange,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::BonBon,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Red,          shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Purple,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Blue,         shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Orange,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Green,        shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Red,          shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Lollipop,     shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Purple,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Yellow,       shortcut: None,     sticky: true,  player: Cell::new(false)},
-        Space {tile: Tile::Blue,         shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Orange,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Green,        shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Red,          shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Purple,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Blue,         shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Orange,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Green,        shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Red,          shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Purple,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::IceCreamCone, shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Blue,         shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Orange,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Green,        shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Red,          shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Purple,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Blue,         shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Orange,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Green,        shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Red,          shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Purple,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Blue,         shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Orange,       shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::Green,        shortcut: None,     sticky: false, player: Cell::new(false)},
-        Space {tile: Tile::End,          shortcut: None,     sticky: false, player: Cell::new(false)},
+        Space {tile: Tile::Start,        shortcut: None,     sticky: false},
+        Space {tile: Tile::Red,          shortcut: None,     sticky: false},
+        Space {tile: Tile::Purple,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Blue,         shortcut: Some(36), sticky: false},
+        Space {tile: Tile::Orange,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Green,        shortcut: None,     sticky: false},
+        Space {tile: Tile::Red,          shortcut: None,     sticky: false},
+        Space {tile: Tile::Purple,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Blue,         shortcut: None,     sticky: false},
+        Space {tile: Tile::Orange,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Green,        shortcut: Some(18), sticky: false},
+        Space {tile: Tile::Red,          shortcut: None,     sticky: false},
+        Space {tile: Tile::Purple,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Blue,         shortcut: None,     sticky: false},
+        Space {tile: Tile::Orange,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Green,        shortcut: None,     sticky: false},
+        Space {tile: Tile::Red,          shortcut: None,     sticky: false},
+        Space {tile: Tile::Purple,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Peppermint,   shortcut: None,     sticky: false},
+        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Blue,         shortcut: None,     sticky: false},
+        Space {tile: Tile::Orange,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Green,        shortcut: None,     sticky: false},
+        Space {tile: Tile::Red,          shortcut: None,     sticky: false},
+        Space {tile: Tile::Purple,       shortcut: None,     sticky: true},
+        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Blue,         shortcut: None,     sticky: false},
+        Space {tile: Tile::Orange,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Green,        shortcut: None,     sticky: false},
+        Space {tile: Tile::Red,          shortcut: None,     sticky: false},
+        Space {tile: Tile::Gumdrop,      shortcut: None,     sticky: false},
+        Space {tile: Tile::Purple,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Blue,         shortcut: None,     sticky: false},
+        Space {tile: Tile::Orange,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Green,        shortcut: None,     sticky: false},
+        Space {tile: Tile::Red,          shortcut: None,     sticky: false},
+        Space {tile: Tile::Purple,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Blue,         shortcut: None,     sticky: false},
+        Space {tile: Tile::Orange,       shortcut: None,     sticky: false},
+        Space {tile: Tile::BonBon,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Red,          shortcut: None,     sticky: false},
+        Space {tile: Tile::Purple,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Blue,         shortcut: None,     sticky: false},
+        Space {tile: Tile::Orange,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Green,        shortcut: None,     sticky: false},
+        Space {tile: Tile::Red,          shortcut: None,     sticky: false},
+        Space {tile: Tile::Lollipop,     shortcut: None,     sticky: false},
+        Space {tile: Tile::Purple,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Yellow,       shortcut: None,     sticky: true},
+        Space {tile: Tile::Blue,         shortcut: None,     sticky: false},
+        Space {tile: Tile::Orange,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Green,        shortcut: None,     sticky: false},
+        Space {tile: Tile::Red,          shortcut: None,     sticky: false},
+        Space {tile: Tile::Purple,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Blue,         shortcut: None,     sticky: false},
+        Space {tile: Tile::Orange,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Green,        shortcut: None,     sticky: false},
+        Space {tile: Tile::Red,          shortcut: None,     sticky: false},
+        Space {tile: Tile::Purple,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false},
+        Space {tile: Tile::IceCreamCone, shortcut: None,     sticky: false},
+        Space {tile: Tile::Blue,         shortcut: None,     sticky: false},
+        Space {tile: Tile::Orange,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Green,        shortcut: None,     sticky: false},
+        Space {tile: Tile::Red,          shortcut: None,     sticky: false},
+        Space {tile: Tile::Purple,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Blue,         shortcut: None,     sticky: false},
+        Space {tile: Tile::Orange,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Green,        shortcut: None,     sticky: false},
+        Space {tile: Tile::Red,          shortcut: None,     sticky: false},
+        Space {tile: Tile::Purple,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Yellow,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Blue,         shortcut: None,     sticky: false},
+        Space {tile: Tile::Orange,       shortcut: None,     sticky: false},
+        Space {tile: Tile::Green,        shortcut: None,     sticky: false},
+        Space {tile: Tile::End,          shortcut: None,     sticky: false},
     ]
 }
 
-pub fn move_player(board: &Vec<Space>, player: &mut Player, card: Card) {
-    if DEBUG {
-        println!("Moving Player {} from space #{}: {} {:?}", player.order, player.space, card.num, card.tile);
+/// A board as plain data, as loaded from (or saved to) a board file
+/// Boards are stored as their list of tiles, plus the shortcuts and licorice spaces on them
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BoardSpec {
+    pub tiles: Vec<Tile>,
+    #[serde(default)]
+    pub shortcuts: Vec<Shortcut>,
+    #[serde(default)]
+    pub sticky: Vec<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Shortcut {
+    pub from: usize,
+    pub to: usize,
+}
+
+/// Everything that can be wrong with a board file
+#[derive(Debug)]
+pub enum BoardError {
+    Io(std::io::Error),
+    Parse(String),
+    StartCount(usize), // Number of Start tiles found, when it isn't exactly one
+    EndCount(usize), // Number of End tiles found, when it isn't exactly one
+    Misplaced(Tile), // Start must be the first tile and End the last
+    SpecialCount { tile: Tile, count: usize }, // Picture tiles must appear exactly once
+    BadShortcut { from: usize, to: usize },
+    BadSticky(usize),
+    Unreachable(Card), // A card in the deck has no space it could take a player to
+}
+
+impl std::fmt::Display for BoardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BoardError::Io(e) => write!(f, "couldn't read board: {}", e),
+            BoardError::Parse(e) => write!(f, "couldn't parse board: {}", e),
+            BoardError::StartCount(n) => write!(f, "board must have exactly one Start tile, found {}", n),
+            BoardError::EndCount(n) => write!(f, "board must have exactly one End tile, found {}", n),
+            BoardError::Misplaced(t) => write!(f, "{:?} tile is in the wrong place", t),
+            BoardError::SpecialCount {tile, count} => write!(f, "{:?} must appear exactly once, found {}", tile, count),
+            BoardError::BadShortcut {from, to} => write!(f, "shortcut from #{} to #{} must lead forward to a space on the board", from, to),
+            BoardError::BadSticky(i) => write!(f, "licorice space #{} must be between Start and End", i),
+            BoardError::Unreachable(c) => write!(f, "no space on the board matches the {} {:?} card", c.num, c.tile),
+        }
     }
-    
-    // Mark the current space the player is on as free, then find the next matching space
-    // If no match is found, we've reached the end
-    let mut s: usize = player.space;
-    board.get(s).unwrap().player.set(false);
+}
+
+impl std::error::Error for BoardError {}
+
+impl Default for BoardSpec {
+    fn default() -> BoardSpec {
+        BoardSpec::from_board(&get_board())
+    }
+}
+
+impl BoardSpec {
+    pub fn from_board(board: &[Space]) -> BoardSpec {
+        BoardSpec {
+            tiles: board.iter().map(|s| s.tile.clone()).collect(),
+            shortcuts: board.iter().enumerate().filter_map(|(from, s)| s.shortcut.map(|to| Shortcut {from, to})).collect(),
+            sticky: board.iter().enumerate().filter(|(_, s)| s.sticky).map(|(i, _)| i).collect(),
+        }
+    }
+
+    /// Lay out a playable board; the spec should have been validated first
+    pub fn build(&self) -> Vec<Space> {
+        let mut board: Vec<Space> = self.tiles.iter()
+            .map(|t| Space {tile: t.clone(), shortcut: None, sticky: false})
+            .collect();
+        for sc in &self.shortcuts {
+            board[sc.from].shortcut = Some(sc.to);
+        }
+        for &i in &self.sticky {
+            board[i].sticky = true;
+        }
+        board
+    }
+
+    /// Load a board file, picking the format from the file extension
+    /// `.toml` and `.json` files hold a serialized `BoardSpec`; anything else is read as text (see `parse_text`)
+    pub fn load(path: &std::path::Path) -> Result<BoardSpec, BoardError> {
+        let text = std::fs::read_to_string(path).map_err(BoardError::Io)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(&text).map_err(|e| BoardError::Parse(e.to_string())),
+            Some("json") => serde_json::from_str(&text).map_err(|e| BoardError::Parse(e.to_string())),
+            _ => BoardSpec::parse_text(&text),
+        }
+    }
+
+    /// Text boards list the tiles in order, separated by whitespace, with `#` starting a comment
+    /// Tile names are case-insensitive (e.g. `start`, `Red`, `IceCreamCone`)
+    /// A `*` suffix marks licorice and a `>N` suffix a shortcut to space #N, e.g. `purple*` or `blue>36`
+    pub fn parse_text(text: &str) -> Result<BoardSpec, BoardError> {
+        let mut spec = BoardSpec {tiles: Vec::new(), shortcuts: Vec::new(), sticky: Vec::new()};
+        for (line_num, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap();
+            for token in line.split_whitespace() {
+                let i = spec.tiles.len();
+                let (name, shortcut) = match token.split_once('>') {
+                    Some((name, to)) => {
+                        let to = to.parse().map_err(|_| BoardError::Parse(format!("line {}: bad shortcut target in '{}'", line_num + 1, token)))?;
+                        (name, Some(to))
+                    }
+                    None => (token, None),
+                };
+                let (name, sticky) = match name.strip_suffix('*') {
+                    Some(name) => (name, true),
+                    None => (name, false),
+                };
+                let tile = parse_tile(name).ok_or_else(|| BoardError::Parse(format!("line {}: unknown tile '{}'", line_num + 1, name)))?;
+                spec.tiles.push(tile);
+                if let Some(to) = shortcut {
+                    spec.shortcuts.push(Shortcut {from: i, to});
+                }
+                if sticky {
+                    spec.sticky.push(i);
+                }
+            }
+        }
+        Ok(spec)
+    }
+
+    /// Write the board in the text format read by `parse_text`, one space per line
+    pub fn to_text(&self) -> String {
+        let mut s = String::new();
+        for (i, t) in self.tiles.iter().enumerate() {
+            s.push_str(&format!("{:?}", t));
+            if self.sticky.contains(&i) {
+                s.push('*');
+            }
+            if let Some(sc) = self.shortcuts.iter().find(|sc| sc.from == i) {
+                s.push_str(&format!(">{}", sc.to));
+            }
+            s.push_str(&format!(" # {}\n", i));
+        }
+        s
+    }
+
+    /// Check the board can be played with the given deck
+    pub fn validate(&self, deck: &[Card]) -> Result<(), BoardError> {
+        let count = |tile: &Tile| self.tiles.iter().filter(|t| *t == tile).count();
+        match count(&Tile::Start) {
+            1 => (),
+            n => return Err(BoardError::StartCount(n)),
+        }
+        match count(&Tile::End) {
+            1 => (),
+            n => return Err(BoardError::EndCount(n)),
+        }
+        if self.tiles.first() != Some(&Tile::Start) {
+            return Err(BoardError::Misplaced(Tile::Start));
+        }
+        if self.tiles.last() != Some(&Tile::End) {
+            return Err(BoardError::Misplaced(Tile::End));
+        }
+        for tile in [Tile::IceCreamCone, Tile::Gumdrop, Tile::Lollipop, Tile::Peppermint, Tile::BonBon] {
+            match count(&tile) {
+                1 => (),
+                count => return Err(BoardError::SpecialCount {tile, count}),
+            }
+        }
+
+        let last = self.tiles.len() - 1;
+        for sc in &self.shortcuts {
+            if sc.from == 0 || sc.from >= last || sc.to <= sc.from || sc.to > last
+                || self.shortcuts.iter().filter(|o| o.from == sc.from).count() > 1 {
+                return Err(BoardError::BadShortcut {from: sc.from, to: sc.to});
+            }
+        }
+        for &i in &self.sticky {
+            if i == 0 || i >= last {
+                return Err(BoardError::BadSticky(i));
+            }
+        }
+
+        for c in deck {
+            if count(&c.tile) == 0 || matches!(c.tile, Tile::Start | Tile::End) {
+                return Err(BoardError::Unreachable(c.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_tile(name: &str) -> Option<Tile> {
+    let tile = match name.to_ascii_lowercase().as_str() {
+        "start" => Tile::Start,
+        "red" => Tile::Red,
+        "orange" => Tile::Orange,
+        "yellow" => Tile::Yellow,
+        "green" => Tile::Green,
+        "blue" => Tile::Blue,
+        "purple" => Tile::Purple,
+        "icecreamcone" => Tile::IceCreamCone,
+        "gumdrop" => Tile::Gumdrop,
+        "lollipop" => Tile::Lollipop,
+        "peppermint" => Tile::Peppermint,
+        "bonbon" => Tile::BonBon,
+        "end" => Tile::End,
+        _ => return None,
+    };
+    Some(tile)
+}
+
+/// The color of the last colored space before the End, if there is one
+pub fn final_color(board: &[Space]) -> Option<Tile> {
+    board.iter().rev().map(|q| &q.tile).find(|t| matches!(t, Tile::Red | Tile::Orange | Tile::Yellow | Tile::Green | Tile::Blue | Tile::Purple)).cloned()
+}
+
+/// Find the space a card sends a player on the given space to, before any shortcut is taken
+/// Colored moves won't finish on a space `occupied` reports as taken; they carry on to the next match instead
+/// None means the end rule doesn't let the card reach the End, so it is lost
+pub(crate) fn landing_space(board: &[Space], from: usize, card: &Card, end: EndRule, occupied: impl Fn(usize) -> bool) -> Option<usize> {
+    let mut s = from;
     match card.tile {
         Tile::Peppermint => s = board.iter().position(|q| q.tile == Tile::Peppermint).unwrap(),
         Tile::Gumdrop => s = board.iter().position(|q| q.tile == Tile::Gumdrop).unwrap(),
@@ -119,36 +323,88 @@ pub fn move_player(board: &Vec<Space>, player: &mut Player, card: Card) {
                 match board.iter().skip(s + 1).position(|q| q.tile == card.tile) { // Skip 1 past the current space to avoid returning the same value from the position call
                     Some(q) => {
                         s += q + 1; // Add the returned position value (+1) as it is relative to the amount skipped
-                        if num >= card.num && !board.get(s).unwrap().player.get() {
+                        if num >= card.num && !occupied(s) {
                             break;
                         }
                     } 
-                    None => {
-                        s = board.iter().position(|q| q.tile == Tile::End).unwrap(); // There's no next one, so the player has reached the end
+                    None => { // There's no next one, so the player has reached the end, if the end rule allows it
+                        let reached = match end {
+                            EndRule::AnyCard => true,
+                            EndRule::ExactColor => final_color(board).as_ref() == Some(&card.tile),
+                            EndRule::Rainbow => num >= card.num,
+                        };
+                        if !reached {
+                            return None;
+                        }
+                        s = board.iter().position(|q| q.tile == Tile::End).unwrap();
                         break;
                     } 
                 }
             }
         }
     }
+    Some(s)
+}
 
-    let space = board.get(s).unwrap();
-    space.player.set(true);
-    player.space = s;
-    player.stuck = space.sticky;
+/// Where a single card took a player
+pub(crate) struct Move {
+    pub from: usize,
+    pub landed: usize, // Space the card matched, before any shortcut was taken
+    pub to: usize, // Space the player finished on
+    pub lost: bool, // The rules didn't let the card move the player at all
+}
 
-    if DEBUG && player.stuck {
-        println!("Player #{} landed on licorice and will be stuck their next turn.", player.order);
+/// Whether a player can't end a color move on `space` because another player is standing there
+/// Picture spaces, Start and End can hold any number of players
+pub(crate) fn taken(board: &[Space], space: usize, mut others: impl Iterator<Item = usize>) -> bool {
+    let shared = matches!(board[space].tile, Tile::Start | Tile::End) || board[space].tile.is_picture();
+    !shared && others.any(|o| o == space)
+}
+
+/// Move player #`i` by a card under the given rules
+/// Where the players stand is the only occupancy there is: with blocking on, a color move skips any space that's taken,
+/// judged by where the player would end up once a mandatory shortcut is followed. An optional shortcut can't be taken
+/// to a space that's taken
+/// `take_shortcut(from, to)` is asked whether to follow a shortcut the player lands on, if the rules make it optional
+pub(crate) fn move_player(board: &[Space], players: &mut [Player], i: usize, card: Card, rules: &Rules, take_shortcut: impl FnOnce(usize, usize) -> bool) -> Move {
+    let blocked = |players: &[Player], space: usize| {
+        rules.blocking && taken(board, space, players.iter().enumerate().filter(|&(j, _)| j != i).map(|(_, o)| o.space))
+    };
+    let from = players[i].space;
+    if DEBUG {
+        println!("Moving Player {} from space #{}: {} {:?}", players[i].order, from, card.num, card.tile);
     }
+    
+    // Find the next matching space; if no match is found, we've reached the end
+    // Only picture cards can send a player backwards
+    let resolved = |s: usize| match (board[s].shortcut, rules.shortcuts) {
+        (Some(to), Shortcuts::Mandatory) => to,
+        _ => s,
+    };
+    let s = match landing_space(board, from, &card, rules.end, |s| blocked(players, resolved(s))) {
+        Some(s) if s >= from || rules.backward_pictures => s,
+        _ => return Move {from, landed: from, to: from, lost: true},
+    };
 
-    if space.shortcut.is_some() {
-        if DEBUG {
-            println!("Space #{} is a shortcut to #{}", s, space.shortcut.unwrap());
+    let space = &board[s];
+    let mut to = s;
+    if let Some(shortcut) = space.shortcut {
+        if rules.shortcuts == Shortcuts::Mandatory || (!blocked(players, shortcut) && take_shortcut(s, shortcut)) {
+            if DEBUG {
+                println!("Space #{} is a shortcut to #{}", s, shortcut);
+            }
+            to = shortcut;
         }
-        player.space = space.shortcut.unwrap();
     }
 
+    let player = &mut players[i];
+    player.space = to;
+    player.stuck = space.sticky;
+    if DEBUG && player.stuck {
+        println!("Player #{} landed on licorice and will be stuck their next turn.", player.order);
+    }
     if DEBUG {
         println!("Player at space #{}", player.space);
     }
-}
\ No newline at end of file
+    Move {from, landed: s, to, lost: false}
+}
@@ -0,0 +1,69 @@
+use crate::*;
+use std::path::Path;
+
+/// A run saved part way through, to be picked up again later
+/// Game #i of a run is always played with `game_rng(seed, i)`, so the seed and the number of games
+/// played are all the RNG state there is to save
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub config: Config,
+    pub completed: u32, // Games #0 up to (not including) this one have been played, and are counted in `summary`
+    pub summary: Summary,
+}
+
+/// Everything that can go wrong reading or writing a checkpoint
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io(std::io::Error),
+    Parse(String),
+}
+
+impl std::fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CheckpointError::Io(e) => write!(f, "couldn't access checkpoint: {}", e),
+            CheckpointError::Parse(e) => write!(f, "couldn't parse checkpoint: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {}
+
+impl Checkpoint {
+    /// A checkpoint at the start of the given run
+    pub fn new(c: &Config) -> Checkpoint {
+        Checkpoint {config: c.clone(), completed: 0, summary: Summary::new(c)}
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.completed >= self.config.games
+    }
+
+    pub fn load(path: &Path) -> Result<Checkpoint, CheckpointError> {
+        let text = std::fs::read_to_string(path).map_err(CheckpointError::Io)?;
+        serde_json::from_str(&text).map_err(|e| CheckpointError::Parse(e.to_string()))
+    }
+
+    /// Write the checkpoint as JSON, replacing the file in one step so an interrupted save leaves the last one intact
+    pub fn save(&self, path: &Path) -> Result<(), CheckpointError> {
+        let text = serde_json::to_string(self).map_err(|e| CheckpointError::Parse(e.to_string()))?;
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, text).map_err(CheckpointError::Io)?;
+        std::fs::rename(&tmp, path).map_err(CheckpointError::Io)
+    }
+
+    /// Play the rest of the run `every` games at a time, handing the checkpoint to `save` after each batch
+    /// The finished summary matches an uninterrupted run with the same config, apart from `elapsed`
+    pub fn run<E>(&mut self, every: u32, mut save: impl FnMut(&Checkpoint) -> Result<(), E>) -> Result<(), E> {
+        let c = &self.config;
+        let setup = Setup::new(c);
+        while !self.is_done() {
+            let end = self.completed.saturating_add(every.max(1)).min(c.games);
+            self.summary.merge(simulate_games(c, &setup, self.completed..end));
+            self.completed = end;
+            save(self)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+use crate::*;
+
+/// A deck as plain data: how many copies of each kind of card it holds
+/// Decks can be loaded from (or saved to) TOML/JSON files, or built up in code, e.g.
+/// `DeckSpec::default().without(Tile::Peppermint).with(Tile::Red, 3, 2)` drops the Peppermint card
+/// and adds two triple red cards
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeckSpec {
+    pub cards: Vec<DeckEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeckEntry {
+    pub tile: Tile,
+    #[serde(default = "one")]
+    pub num: u32, // Spaces of the color to move; always 1 for picture cards
+    pub count: u32, // Copies of this card in the deck
+}
+
+fn one() -> u32 {
+    1
+}
+
+/// Everything that can be wrong with a deck file
+#[derive(Debug)]
+pub enum DeckError {
+    Io(std::io::Error),
+    Parse(String),
+    Empty,
+    BadCard { tile: Tile, num: u32 }, // Start/End cards, zero-space cards or multi-space picture cards
+    NotOnBoard(Tile), // The board has no space the card could take a player to
+}
+
+impl std::fmt::Display for DeckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DeckError::Io(e) => write!(f, "couldn't read deck: {}", e),
+            DeckError::Parse(e) => write!(f, "couldn't parse deck: {}", e),
+            DeckError::Empty => write!(f, "deck has no cards"),
+            DeckError::BadCard {tile, num} => write!(f, "a {} {:?} card can't be part of a deck", num, tile),
+            DeckError::NotOnBoard(t) => write!(f, "the board has no {:?} space", t),
+        }
+    }
+}
+
+impl std::error::Error for DeckError {}
+
+impl Default for DeckSpec {
+    fn default() -> DeckSpec {
+        DeckSpec::from_deck(&make_deck())
+    }
+}
+
+impl DeckSpec {
+    pub fn empty() -> DeckSpec {
+        DeckSpec {cards: Vec::new()}
+    }
+
+    /// Count up the cards of an existing deck
+    pub fn from_deck(deck: &[Card]) -> DeckSpec {
+        deck.iter().fold(DeckSpec::empty(), |spec, c| spec.with(c.tile.clone(), c.num, 1))
+    }
+
+    /// Add `count` copies of the given card
+    pub fn with(mut self, tile: Tile, num: u32, count: u32) -> DeckSpec {
+        match self.cards.iter_mut().find(|e| e.tile == tile && e.num == num) {
+            Some(e) => e.count += count,
+            None => self.cards.push(DeckEntry {tile, num, count}),
+        }
+        self
+    }
+
+    /// Remove every card of the given tile
+    pub fn without(mut self, tile: Tile) -> DeckSpec {
+        self.cards.retain(|e| e.tile != tile);
+        self
+    }
+
+    /// Number of cards in the deck
+    pub fn len(&self) -> u32 {
+        self.cards.iter().map(|e| e.count).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Lay out the cards of the deck, in no particular order
+    pub fn build(&self) -> Vec<Card> {
+        let mut d = Vec::with_capacity(self.len() as usize);
+        for e in &self.cards {
+            for _ in 0..e.count {
+                d.push(Card {tile: e.tile.clone(), num: e.num});
+            }
+        }
+        d
+    }
+
+    /// Load a deck file, picking TOML or JSON from the file extension (TOML if it's neither)
+    pub fn load(path: &std::path::Path) -> Result<DeckSpec, DeckError> {
+        let text = std::fs::read_to_string(path).map_err(DeckError::Io)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str(&text).map_err(|e| DeckError::Parse(e.to_string())),
+            _ => toml::from_str(&text).map_err(|e| DeckError::Parse(e.to_string())),
+        }
+    }
+
+    /// Check the deck can be played on the given board
+    pub fn validate(&self, board: &BoardSpec) -> Result<(), DeckError> {
+        if self.is_empty() {
+            return Err(DeckError::Empty);
+        }
+        for e in &self.cards {
+            if matches!(e.tile, Tile::Start | Tile::End) || e.num == 0 || (e.tile.is_picture() && e.num != 1) {
+                return Err(DeckError::BadCard {tile: e.tile.clone(), num: e.num});
+            }
+            if e.count > 0 && !board.tiles.contains(&e.tile) {
+                return Err(DeckError::NotOnBoard(e.tile.clone()));
+            }
+        }
+        Ok(())
+    }
+}
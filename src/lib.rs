@@ -1,47 +1,155 @@
+use std::collections::{BTreeMap, HashMap};
 use std::time::Instant;
-use rand::{thread_rng, prelude::SliceRandom};
+use rand::{thread_rng, Rng, SeedableRng, prelude::SliceRandom};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
+use crate::agents::*;
+use crate::analytics::*;
 use crate::board::*;
+use crate::deck::*;
+use crate::rules::*;
+use crate::stats::*;
+use crate::trace::*;
 
-mod board;
+pub mod agents;
+pub mod analytics;
+pub mod board;
+pub mod checkpoint;
+pub mod deck;
+pub mod markov;
+pub mod rules;
+pub mod search;
+pub mod sensitivity;
+pub mod stats;
+pub mod trace;
 
 const DEBUG: bool = false;
 
-struct Stats {
-    turns: u32,
-    winner: u32,
-    infinite: bool,
+/// Turns a fixed-deck game is stopped after when an agent may choose at random and the config sets no `max_turns`
+/// A repeated state only proves a loop while no random choice is made, so such a game could otherwise run forever
+pub const RANDOM_CHOICE_MAX_TURNS: u32 = 100_000;
+
+/// How many unfinished shuffled games a `Summary` lists, so it stays the same size however many games are played
+pub const UNFINISHED_GAMES_KEPT: usize = 100;
+
+/// The outcome of a single game
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Stats {
+    pub turns: u32,
+    pub winner: u32,
+    pub reshuffles: u32,
+    pub infinite: bool,
+    pub cycle: Option<Cycle>, // Where an infinite game started repeating itself
+    pub over_limit: bool, // Stopped unfinished after the config's `max_turns`
+    pub deck: Option<Vec<Card>>, // The starting deck, kept for games that didn't finish
+    pub cards_drawn: u32,
+    pub cards: CardTable,
+    pub lead_changes: u32,
+    pub comeback: u32, // How far the winner was ever behind the leader
+    pub seats: Vec<SeatStats>,
+    pub spaces: Vec<SpaceStats>, // What happened on each space of the board
 }
 
-struct Summary {
-    turns: Vec<u32>, // Number of turns for each game
-    winners: Vec<u32>, // A player-sized vector counting how many wins each player has
+/// A fixed deck that loops forever: the game was in the same state at two deck exhaustions
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Cycle {
+    pub start: u32, // Turn the repeated state was first seen on
+    pub length: u32, // Turns before it came around again
 }
 
-#[derive(Debug)]
-pub enum PlayerCount {
-    Two,
-    Three,
-    Four,
+/// The results of a batch of games
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Summary {
+    pub turns: TurnAccumulator, // Number of turns each game took
+    pub winners: Vec<u32>, // A player-sized vector counting how many wins each player has
+    pub elapsed: f32, // Wall-clock time of the run in seconds
+    pub reshuffles: u64, // Total number of times a deck ran out and was reshuffled
+    pub cards: CardTable, // What the cards drawn in every game did
+    pub seats: Vec<SeatStats>, // How the race went for each seat
+    pub spaces: Vec<SpaceStats>, // What happened on each space of the board
+    pub infinite: u64, // Games that looped forever
+    pub over_limit: u64, // Games stopped unfinished after `max_turns`
+    pub unfinished_decks: BTreeMap<String, u64>, // With a fixed deck: the deck (in `write_deck` format where possible) and how many games it left unfinished
+    pub unfinished_games: Vec<u64>, // With shuffled decks: the first `UNFINISHED_GAMES_KEPT` unfinished games, to replay by index
+    pub seed: u64, // Master seed the run was played with
 }
 
+/// The number of players at the table, from solitaire up to `PlayerCount::MAX`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "u32", into = "u32")]
+pub struct PlayerCount(u32);
+
 impl PlayerCount {
-    fn value(&self) -> u32 {
-        match *self {
-            PlayerCount::Two => 2,
-            PlayerCount::Three => 3,
-            PlayerCount::Four => 4,
+    pub const MAX: u32 = 256;
+
+    pub fn new(n: u32) -> Result<PlayerCount, PlayerCountError> {
+        if n == 0 || n > PlayerCount::MAX {
+            return Err(PlayerCountError(n));
         }
+        Ok(PlayerCount(n))
+    }
+
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+}
+
+impl TryFrom<u32> for PlayerCount {
+    type Error = PlayerCountError;
+
+    fn try_from(n: u32) -> Result<PlayerCount, PlayerCountError> {
+        PlayerCount::new(n)
+    }
+}
+
+impl From<PlayerCount> for u32 {
+    fn from(p: PlayerCount) -> u32 {
+        p.0
+    }
+}
+
+impl std::str::FromStr for PlayerCount {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<PlayerCount, String> {
+        let n: u32 = s.parse().map_err(|_| format!("'{}' is not a number", s))?;
+        PlayerCount::new(n).map_err(|e| e.to_string())
     }
 }
 
+impl std::fmt::Display for PlayerCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A player count outside of 1..=`PlayerCount::MAX`
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayerCountError(pub u32);
+
+impl std::fmt::Display for PlayerCountError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "player count must be between 1 and {}, got {}", PlayerCount::MAX, self.0)
+    }
+}
+
+impl std::error::Error for PlayerCountError {}
+
 struct Player {
     order: u32,
     space: usize,
     stuck: bool,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+/// Everything that decides how a game with a fixed deck continues, taken whenever the deck runs out
+#[derive(PartialEq, Eq, Hash)]
+struct GameState {
+    turn: usize, // Index of the player about to draw
+    players: Vec<(usize, bool)>, // Each player's space and whether they're stuck, which also settles which spaces are taken
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Tile {
     Start,
     Red,
@@ -58,101 +166,417 @@ pub enum Tile {
     End,
 }
 
-#[derive(Clone, Debug)]
+impl Tile {
+    /// Whether this is one of the picture spaces a card sends players straight to
+    pub fn is_picture(&self) -> bool {
+        matches!(self, Tile::IceCreamCone | Tile::Gumdrop | Tile::Lollipop | Tile::Peppermint | Tile::BonBon)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Card {
-    tile: Tile,
-    num: u32,
+    pub tile: Tile,
+    pub num: u32,
+}
+
+/// Everything needed to describe (and reproduce) a batch of games
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+    pub players: PlayerCount,
+    pub games: u32,
+    pub deck: Option<Vec<Card>>, // A fixed deck that is never shuffled; None shuffles a deck built from `deck_spec`
+    pub deck_spec: DeckSpec,
+    pub board: BoardSpec,
+    pub seed: u64,
+    pub threads: usize, // Number of worker threads the games are split across
+    pub max_turns: Option<u32>, // Stop a game unfinished once it has gone on this long (see `RANDOM_CHOICE_MAX_TURNS` for the default)
+    pub rules: Rules,
+    pub agents: Vec<AgentKind>, // Who makes each seat's choices; seats past the end of the list are Random
+}
+
+impl Config {
+    /// A config for the given players/games with a random seed, using every available core
+    pub fn new(players: PlayerCount, games: u32) -> Config {
+        Config {
+            players,
+            games,
+            deck: None,
+            deck_spec: DeckSpec::default(),
+            board: BoardSpec::default(),
+            seed: thread_rng().gen(),
+            threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            max_turns: None,
+            rules: Rules::default(),
+            agents: Vec::new(),
+        }
+    }
+}
+
+/// Why a config can't be played
+#[derive(Debug)]
+pub enum ConfigError {
+    Composition(DeckError),
+    EmptyDeck, // The fixed deck has no cards
+    Board(BoardError),
+    Rules(UnreachableEnd),
+    Agents { agents: usize, players: u32 }, // More agents were given than there are seats
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ConfigError::Composition(e) => write!(f, "invalid deck composition: {}", e),
+            ConfigError::EmptyDeck => write!(f, "invalid deck: deck has no cards"),
+            ConfigError::Board(e) => write!(f, "invalid board: {}", e),
+            ConfigError::Rules(e) => write!(f, "invalid rules: {}", e),
+            ConfigError::Agents {agents, players} => write!(f, "got {} agents for {} players", agents, players),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Check the games can be played: the board, the deck (fixed, or else the composition it's shuffled from) and the rules fit together
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        match &self.deck {
+            None => self.deck_spec.validate(&self.board).map_err(ConfigError::Composition)?,
+            Some(deck) if deck.is_empty() => return Err(ConfigError::EmptyDeck),
+            Some(_) => (),
+        }
+        let deck = self.deck.clone().unwrap_or_else(|| self.deck_spec.build());
+        self.board.validate(&deck).map_err(ConfigError::Board)?;
+        self.rules.validate(&self.board, &deck).map_err(ConfigError::Rules)?;
+        if self.agents.len() > self.players.value() as usize {
+            return Err(ConfigError::Agents {agents: self.agents.len(), players: self.players.value()});
+        }
+        Ok(())
+    }
+}
+
+/// What every game of a run shares: the board laid out from the config's `board`, and each seat's agent
+pub(crate) struct Setup {
+    board: Vec<Space>,
+    agents: Vec<Box<dyn PlayerAgent>>,
+    random_choices: bool, // Some agent may be asked to choose, and chooses at random
 }
 
-/// Calculate the stats from a given number of players/desired games
+impl Setup {
+    pub(crate) fn new(c: &Config) -> Setup {
+        let agents = (0..c.players.value() as usize)
+            .map(|i| c.agents.get(i).unwrap_or(&AgentKind::Random).build(c))
+            .collect::<Vec<_>>();
+        let random_choices = c.rules.shortcuts == Shortcuts::Optional && agents.iter().any(|a| a.is_random());
+        Setup {board: c.board.build(), agents, random_choices}
+    }
+}
+
+/// Calculate the stats from a given config and print them
 /// If a deck is provided it will not be shuffled
 /// Otherwise, a randomly generated deck will be used
-pub fn calculate(p: PlayerCount, num_games: u32, deck: Option<Vec<Card>>) {
-    // Setup summary for runs
-    let mut s = match p {
-        PlayerCount::Two => Summary{turns: Vec::new(), winners: vec![0; 2]},
-        PlayerCount::Three => Summary{turns: Vec::new(), winners: vec![0; 3]},
-        PlayerCount::Four => Summary{turns: Vec::new(), winners: vec![0; 4]},
-    };
-    
-    // Get current time and run the desired number of games
+pub fn calculate(c: &Config) {
+    let s = simulate(c);
+    println!("Done! Ran {} game(s) in {:.3} s (seed {})", c.games, s.elapsed, s.seed);
+    print_summary(&s);
+}
+
+/// Run the configured games and return the collected results
+/// The config should have passed `Config::validate`; an invalid board or an empty deck panics
+/// Game #i of a run is shuffled with `game_rng(seed, i)`, so it can be replayed on its own
+/// and the results don't depend on how many threads the games were split across
+pub fn simulate(c: &Config) -> Summary {
+    simulate_games(c, &Setup::new(c), 0..c.games)
+}
+
+/// Play the given range of games from a run, split across the configured number of threads
+fn simulate_games(c: &Config, setup: &Setup, games: std::ops::Range<u32>) -> Summary {
     let now = Instant::now();
-    for _ in 0..num_games {
-        let stats = play(&p, deck.clone());
-        if stats.infinite {
-            return;
+    let count = games.end - games.start;
+    let threads = (c.threads.max(1) as u32).min(count.max(1));
+    let chunk = count.div_ceil(threads);
+
+    // Each worker plays a contiguous range of games, and the ranges are merged back in order
+    let parts: Vec<Summary> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|t| {
+                let start = games.start + (t * chunk).min(count);
+                let end = games.start + ((t + 1) * chunk).min(count);
+                scope.spawn(move || run_games(c, setup, start..end))
+            })
+            .collect();
+        workers.into_iter().map(|w| w.join().unwrap()).collect()
+    });
+
+    let mut s = Summary::new(c);
+    for part in parts {
+        s.merge(part);
+    }
+    s.elapsed = now.elapsed().as_millis() as f32 / 1000.0;
+    s
+}
+
+/// Play the given range of games from a run on the current thread
+fn run_games(c: &Config, setup: &Setup, games: std::ops::Range<u32>) -> Summary {
+    let mut s = Summary::new(c);
+    for i in games {
+        let stats = play(c, setup, c.deck.clone(), &mut game_rng(c.seed, i as u64), None);
+        s.reshuffles += stats.reshuffles as u64;
+        if stats.infinite || stats.over_limit {
+            s.infinite += stats.infinite as u64;
+            s.over_limit += stats.over_limit as u64;
+            if c.deck.is_some() {
+                let deck = deck_name(stats.deck.as_deref().unwrap());
+                *s.unfinished_decks.entry(deck).or_insert(0) += 1;
+            } else if s.unfinished_games.len() < UNFINISHED_GAMES_KEPT {
+                // A shuffled game is replayed exactly from the seed and its index
+                s.unfinished_games.push(i as u64);
+            }
+            continue;
+        }
+        s.turns.add(stats.turns);
+        s.cards.merge(&stats.cards);
+        for (seat, o) in s.seats.iter_mut().zip(&stats.seats) {
+            seat.merge(o);
+        }
+        for (space, o) in s.spaces.iter_mut().zip(&stats.spaces) {
+            space.merge(o);
         }
-        s.turns.push(stats.turns);
         *s.winners.get_mut((stats.winner - 1) as usize).unwrap() += 1;
     }
-    let elapsed = now.elapsed().as_millis() as f32/ 1000.0 ;
-    println!("Done! Ran {} game(s) in {:.3} s", num_games, elapsed);
-    print_summary(&s);
+    s
 }
 
-/// Play 1 round of CandyLand with the given number of players
-fn play(p: &PlayerCount, deck: Option<Vec<Card>>) -> Stats {
+impl Summary {
+    /// An empty summary, ready to count the config's games
+    pub fn new(c: &Config) -> Summary {
+        let n = c.players.value() as usize;
+        Summary{winners: vec![0; n], seats: vec![SeatStats::default(); n], spaces: vec![SpaceStats::default(); c.board.tiles.len()],
+            seed: c.seed, ..Default::default()}
+    }
+
+    /// Fold the results of a later batch of games into this one
+    pub fn merge(&mut self, other: Summary) {
+        self.turns.merge(&other.turns);
+        for (w, o) in self.winners.iter_mut().zip(other.winners) {
+            *w += o;
+        }
+        self.elapsed += other.elapsed;
+        self.reshuffles += other.reshuffles;
+        self.cards.merge(&other.cards);
+        for (seat, o) in self.seats.iter_mut().zip(&other.seats) {
+            seat.merge(o);
+        }
+        for (space, o) in self.spaces.iter_mut().zip(&other.spaces) {
+            space.merge(o);
+        }
+        self.infinite += other.infinite;
+        self.over_limit += other.over_limit;
+        for (deck, n) in other.unfinished_decks {
+            *self.unfinished_decks.entry(deck).or_insert(0) += n;
+        }
+        self.unfinished_games.extend(other.unfinished_games);
+        self.unfinished_games.truncate(UNFINISHED_GAMES_KEPT);
+    }
+
+    /// Number of games played, finished or not
+    pub fn games(&self) -> u64 {
+        self.turns.count + self.infinite + self.over_limit
+    }
+}
+
+/// Replay a single game from the run described by the given config, given the game's index within it
+/// Like `simulate`, this panics on a config that doesn't pass `Config::validate`
+pub fn replay(c: &Config, game: u64) -> Stats {
+    play(c, &Setup::new(c), c.deck.clone(), &mut game_rng(c.seed, game), None)
+}
+
+/// Replay a single game like `replay`, also recording everything that happened in it
+pub fn replay_traced(c: &Config, game: u64) -> (Stats, Trace) {
+    let mut trace = Trace::default();
+    let stats = play(c, &Setup::new(c), c.deck.clone(), &mut game_rng(c.seed, game), Some(&mut trace));
+    (stats, trace)
+}
+
+/// The random number generator used to shuffle (and reshuffle) the deck of a given game
+/// Each game of a run gets its own ChaCha stream, so games don't depend on each other
+pub fn game_rng(seed: u64, game: u64) -> ChaCha8Rng {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(game);
+    rng
+}
+
+/// Play 1 round of CandyLand with the configured players and deck, on the setup's board with its agents
+/// If a fixed deck is given it will not be shuffled; otherwise a deck is built from the config's `deck_spec`
+/// If a trace is given, every turn of the game is recorded into it
+fn play<R: Rng>(c: &Config, setup: &Setup, deck: Option<Vec<Card>>, rng: &mut R, mut trace: Option<&mut Trace>) -> Stats {
     // Setup players
     let mut players: Vec<Player> = Vec::new();
-    for i in 0..p.value() {
+    for i in 0..c.players.value() {
         players.push(Player {order: i + 1, space: 0, stuck: false});
     }
 
     // Setup deck
-    let allow_reshuffle: bool;
-    let mut d = if deck.is_none() {
-        allow_reshuffle = true;
-        make_deck()
-    } else {
-        allow_reshuffle = false;
-        deck.unwrap()
-    };
+    let allow_reshuffle = deck.is_none();
+    let mut d = deck.unwrap_or_else(|| c.deck_spec.build());
     if allow_reshuffle {
-        d.shuffle(&mut thread_rng())
+        d.shuffle(rng)
     }
     let deck_copy = d.clone(); // Cache off a deck for if/when we have to reshuffle
     d.reverse(); // Deck order is reversed so we can pull from the end
 
     // Let's do that Candy Land
-    let board = get_board();
+    let board = &setup.board[..];
     let mut winner = 0;
     let mut num_turns = 0;
     let mut player_won = false;
     let mut reshuffle = 0;
+    let mut cards = CardTable::default();
+    let mut setbacks: Vec<(u32, Card)> = Vec::new(); // Leaders sent backwards, and by which card
+    let mut seats = vec![SeatStats::default(); players.len()];
+    let mut spaces = vec![SpaceStats::default(); board.len()];
+    let mut deficits = vec![0; players.len()]; // Farthest each player has been behind the leader
+    let mut leader: Option<usize> = None; // The last player to be the sole leader
+    let mut lead_changes = 0;
+    let mut exhaustions: HashMap<GameState, u32> = HashMap::new(); // Turn each state was seen on
+    let max_turns = c.max_turns.or((!allow_reshuffle && setup.random_choices).then_some(RANDOM_CHOICE_MAX_TURNS));
     while !player_won {
-        for mut p in &mut players {
+        for i in 0..players.len() {
+            // The sole leader is ahead of every other player
+            let leading = players.iter().enumerate().all(|(j, o)| j == i || o.space < players[i].space);
+            if max_turns.is_some_and(|m| num_turns >= m) {
+                let cards_drawn = cards.drawn.total() as u32;
+                return Stats{turns: num_turns, winner: 0, reshuffles: reshuffle, infinite: false, cycle: None, over_limit: true,
+                    deck: Some(deck_copy), cards_drawn, cards, lead_changes, comeback: 0, seats, spaces};
+            }
+            let p = &mut players[i];
             num_turns += 1;
-            if p.stuck {
+            if p.stuck && c.rules.licorice == Licorice::LoseTurn {
                 if DEBUG {
                     println!("Player #{} is stuck. Skipping turn...", p.order);
                 }
+                if let Some(t) = trace.as_deref_mut() {
+                    t.record(Event::Skip {turn: num_turns, player: p.order, space: p.space});
+                }
                 p.stuck = false;
                 continue;
             }
     
-            let c = match d.pop() {
+            let card = match d.pop() {
                 Some(c) => c,
                 None => {
                     reshuffle += 1;
+                    if let Some(t) = trace.as_deref_mut() {
+                        t.record(Event::Reshuffle {turn: num_turns, count: reshuffle});
+                    }
+
+                    // A fixed deck plays out the same way from the same state, so seeing one twice means a loop
+                    // (as long as no agent made a random choice in between)
+                    if !allow_reshuffle {
+                        let state = GameState {
+                            turn: i,
+                            players: players.iter().map(|o| (o.space, o.stuck)).collect(),
+                        };
+                        if let Some(&start) = exhaustions.get(&state) {
+                            let cycle = Cycle {start, length: num_turns - start};
+                            if let Some(t) = trace.as_deref_mut() {
+                                t.record(Event::Cycle {turn: num_turns, start, length: cycle.length});
+                            }
+                            let cards_drawn = cards.drawn.total() as u32;
+                            return Stats{turns: 0, winner: 0, reshuffles: reshuffle, infinite: true, cycle: Some(cycle), over_limit: false,
+                                deck: Some(deck_copy), cards_drawn, cards, lead_changes, comeback: 0, seats, spaces};
+                        }
+                        exhaustions.insert(state, num_turns);
+                    }
+
                     d = deck_copy.clone();
                     if allow_reshuffle {                        
-                        d.shuffle(&mut thread_rng());
+                        d.shuffle(rng);
                     }
                     d.reverse();
                     d.pop().unwrap()
                 }
             };
-            
-            if reshuffle >= 3 && !allow_reshuffle {
-                println!("Deck was an infinite loop: {}", write_deck(&deck_copy));
-                return Stats{turns: 0, winner: 0, infinite: true};
+            let p = &mut players[i];
+
+            if let Some(t) = trace.as_deref_mut() {
+                t.record(Event::Draw {turn: num_turns, player: p.order, tile: card.tile.clone(), num: card.num});
+            }
+            cards.drawn.add(&card);
+            if p.stuck {
+                // Stuck on licorice until drawing its color
+                if card.tile != board[p.space].tile {
+                    if let Some(t) = trace.as_deref_mut() {
+                        t.record(Event::Skip {turn: num_turns, player: p.order, space: p.space});
+                    }
+                    continue;
+                }
+                p.stuck = false;
+            }
+            let drawn = card.clone();
+            let mut chose_randomly = false;
+            let m = move_player(board, &mut players, i, card, &c.rules, |from, to| {
+                chose_randomly |= setup.agents[i].is_random();
+                setup.agents[i].take_shortcut(board, from, to, rng)
+            });
+            if chose_randomly {
+                // The states seen so far were followed by a random choice, so seeing one again proves nothing
+                exhaustions.clear();
+            }
+            let p = &players[i];
+            if m.to < m.from {
+                seats[i].backward_moves += 1;
+                seats[i].spaces_lost += (m.from - m.to) as u64;
+                cards.backward.add(&drawn);
+                if leading {
+                    cards.leader_setbacks.add(&drawn);
+                    setbacks.push((p.order, drawn.clone()));
+                }
+            }
+            if let Some(t) = trace.as_deref_mut() {
+                t.record(Event::Move {turn: num_turns, player: p.order, from: m.from, to: m.landed});
+                if m.to != m.landed {
+                    t.record(Event::Shortcut {turn: num_turns, player: p.order, from: m.landed, to: m.to});
+                }
+                if p.stuck {
+                    t.record(Event::Stuck {turn: num_turns, player: p.order, space: m.landed});
+                }
             }
 
-            move_player(&board, &mut p, c);
+            if !m.lost {
+                spaces[m.landed].landings += 1;
+                if drawn.tile.is_picture() {
+                    spaces[m.landed].pictures += 1;
+                }
+                if m.to != m.landed {
+                    spaces[m.landed].shortcuts += 1;
+                }
+                if players[i].stuck {
+                    spaces[m.landed].licorice_stops += 1;
+                }
+            }
+
+            // Track who's in front, and how far behind everyone else is
+            let front = players.iter().map(|o| o.space).max().unwrap();
+            for (d, o) in deficits.iter_mut().zip(&players) {
+                *d = (*d).max((front - o.space) as u32);
+            }
+            let mut in_front = players.iter().enumerate().filter(|(_, o)| o.space == front);
+            if let (Some((j, _)), None) = (in_front.next(), in_front.next()) {
+                if leader.is_some_and(|l| l != j) {
+                    lead_changes += 1;
+                    seats[j].lead_changes += 1;
+                }
+                leader = Some(j);
+            }
+
+            let p = &mut players[i];
             if p.space == (board.len() - 1) {
                 if DEBUG {
                     println!("Player {} won!", p.order);   
                 }
+                if let Some(t) = trace.as_deref_mut() {
+                    t.record(Event::Win {turn: num_turns, player: p.order});
+                }
                 winner = p.order;
                 player_won = true;
                 break;
@@ -162,10 +586,20 @@ fn play(p: &PlayerCount, deck: Option<Vec<Card>>) -> Stats {
     if DEBUG {
         println!("Done! {} turns", num_turns);
     }
-    Stats{turns: num_turns, winner: winner, infinite: false}
+    for (order, card) in setbacks {
+        if order != winner {
+            cards.leader_setback_losses.add(&card);
+        }
+    }
+    let cards_drawn = cards.drawn.total() as u32;
+    let comeback = deficits[winner as usize - 1];
+    seats[winner as usize - 1].comebacks = comeback as u64;
+    seats[winner as usize - 1].largest_comeback = comeback;
+    Stats{turns: num_turns, winner, reshuffles: reshuffle, infinite: false, cycle: None, over_limit: false, deck: None, cards_drawn, cards, lead_changes, comeback, seats, spaces}
 }
 
-fn make_deck() -> Vec<Card> {
+/// The standard 66 card deck
+pub fn make_deck() -> Vec<Card> {
     let mut c: Vec<Card> = Vec::new();
     for _ in 0..3 {
         c.push(Card {tile: Tile::Red, num: 1});
@@ -191,60 +625,61 @@ fn make_deck() -> Vec<Card> {
     c.push(Card {tile: Tile::Peppermint, num: 1});
     c.push(Card {tile: Tile::BonBon, num: 1});
 
-    return c;
+    c
 }
 
 pub fn get_infinite_two_person_deck() -> Vec<Card> {
-    let mut c: Vec<Card> = Vec::new();
-    c.push(Card {tile: Tile::Peppermint, num: 1});
-    c.push(Card {tile: Tile::Gumdrop, num: 1});
-    c.push(Card {tile: Tile::Yellow, num: 1});
-    c.push(Card {tile: Tile::Purple, num: 1});
-    c.push(Card {tile: Tile::Blue, num: 1});
-    c.push(Card {tile: Tile::Yellow, num: 1});
-    c.push(Card {tile: Tile::Orange, num: 1});
-    c.push(Card {tile: Tile::Blue, num: 1});
-    c.push(Card {tile: Tile::Green, num: 1});
-    c.push(Card {tile: Tile::Orange, num: 1});
-    c.push(Card {tile: Tile::Red, num: 1});
-    c.push(Card {tile: Tile::Green, num: 1});
-    c.push(Card {tile: Tile::Purple, num: 1});
-    c.push(Card {tile: Tile::Red, num: 2});
-    c.push(Card {tile: Tile::Purple, num: 1});
-    c.push(Card {tile: Tile::Yellow, num: 1});
-    c.push(Card {tile: Tile::Yellow, num: 1});
-    c.push(Card {tile: Tile::Blue, num: 1});
-    c.push(Card {tile: Tile::Blue, num: 2});
-    c.push(Card {tile: Tile::Orange, num: 2});
-    c.push(Card {tile: Tile::Orange, num: 2});
-    c.push(Card {tile: Tile::Red, num: 2});
-    c.push(Card {tile: Tile::Purple, num: 2});
-    c.push(Card {tile: Tile::Purple, num: 2});
-    c.push(Card {tile: Tile::Yellow, num: 2});
-    c.push(Card {tile: Tile::Yellow, num: 2});
-    c.push(Card {tile: Tile::BonBon, num: 1});
-    c.push(Card {tile: Tile::Orange, num: 1});
-    c.push(Card {tile: Tile::Green, num: 2});
-    c.push(Card {tile: Tile::Green, num: 2});
-    c.push(Card {tile: Tile::Red, num: 1});
-    c.push(Card {tile: Tile::Orange, num: 1});
-    c.push(Card {tile: Tile::Purple, num: 2});
-    c.push(Card {tile: Tile::Green, num: 2});
-    c.push(Card {tile: Tile::Yellow, num: 2});
-    c.push(Card {tile: Tile::Lollipop, num: 1});
-    c.push(Card {tile: Tile::Green, num: 1});
-    c.push(Card {tile: Tile::Blue, num: 2});
-    c.push(Card {tile: Tile::Red, num: 1});
-    c.push(Card {tile: Tile::Orange, num: 2});
-    c.push(Card {tile: Tile::IceCreamCone, num: 1});
-    c.push(Card {tile: Tile::Green, num: 1});
-    c.push(Card {tile: Tile::Blue, num: 2});
-    c.push(Card {tile: Tile::Red, num: 2});
-    return c;
+    vec![
+        Card {tile: Tile::Peppermint, num: 1},
+        Card {tile: Tile::Gumdrop, num: 1},
+        Card {tile: Tile::Yellow, num: 1},
+        Card {tile: Tile::Purple, num: 1},
+        Card {tile: Tile::Blue, num: 1},
+        Card {tile: Tile::Yellow, num: 1},
+        Card {tile: Tile::Orange, num: 1},
+        Card {tile: Tile::Blue, num: 1},
+        Card {tile: Tile::Green, num: 1},
+        Card {tile: Tile::Orange, num: 1},
+        Card {tile: Tile::Red, num: 1},
+        Card {tile: Tile::Green, num: 1},
+        Card {tile: Tile::Purple, num: 1},
+        Card {tile: Tile::Red, num: 2},
+        Card {tile: Tile::Purple, num: 1},
+        Card {tile: Tile::Yellow, num: 1},
+        Card {tile: Tile::Yellow, num: 1},
+        Card {tile: Tile::Blue, num: 1},
+        Card {tile: Tile::Blue, num: 2},
+        Card {tile: Tile::Orange, num: 2},
+        Card {tile: Tile::Orange, num: 2},
+        Card {tile: Tile::Red, num: 2},
+        Card {tile: Tile::Purple, num: 2},
+        Card {tile: Tile::Purple, num: 2},
+        Card {tile: Tile::Yellow, num: 2},
+        Card {tile: Tile::Yellow, num: 2},
+        Card {tile: Tile::BonBon, num: 1},
+        Card {tile: Tile::Orange, num: 1},
+        Card {tile: Tile::Green, num: 2},
+        Card {tile: Tile::Green, num: 2},
+        Card {tile: Tile::Red, num: 1},
+        Card {tile: Tile::Orange, num: 1},
+        Card {tile: Tile::Purple, num: 2},
+        Card {tile: Tile::Green, num: 2},
+        Card {tile: Tile::Yellow, num: 2},
+        Card {tile: Tile::Lollipop, num: 1},
+        Card {tile: Tile::Green, num: 1},
+        Card {tile: Tile::Blue, num: 2},
+        Card {tile: Tile::Red, num: 1},
+        Card {tile: Tile::Orange, num: 2},
+        Card {tile: Tile::IceCreamCone, num: 1},
+        Card {tile: Tile::Green, num: 1},
+        Card {tile: Tile::Blue, num: 2},
+        Card {tile: Tile::Red, num: 2},
+    ]
 }
 
 /// Decks are encoded with a single letter representing each card type.
 /// For colored cards, lowercase indicates a one tile, uppercase two tiles.
+/// Cards moving more than two tiles have no letter.
 /// Card to letter encodings are as follows:
 ///  - Red/Orange/Yellow/Green/Blue/Purple as R/O/Y/G/B/P
 ///  - Ice Cream Code as I
@@ -252,55 +687,132 @@ pub fn get_infinite_two_person_deck() -> Vec<Card> {
 ///  - Lollipop as L
 ///  - Peppermint as E
 ///  - Bon Bon as N
-fn write_deck(cards: &Vec<Card>) -> String {
+pub fn write_deck(cards: &[Card]) -> Result<String, DeckWriteError> {
     let mut s = String::new();
-    for c in cards {
-        let mut temp = match c.tile {
-            Tile::Red => String::from("r"),
-            Tile::Orange => String::from("o"),
-            Tile::Yellow => String::from("y"),
-            Tile::Green => String::from("g"),
-            Tile::Blue => String::from("b"),
-            Tile::Purple => String::from("p"),
-            Tile::IceCreamCone => String::from("I"),
-            Tile::Gumdrop => String::from("U"),
-            Tile::Lollipop => String::from("L"),
-            Tile::Peppermint => String::from("E"),
-            Tile::BonBon => String::from("N"),
-            _ => {
-                eprintln!("Invalid type {:?} found in card list!", c.tile);
-                String::from("ERROR")
-            },
+    for (i, c) in cards.iter().enumerate() {
+        let letter = match c.tile {
+            Tile::Red => 'r',
+            Tile::Orange => 'o',
+            Tile::Yellow => 'y',
+            Tile::Green => 'g',
+            Tile::Blue => 'b',
+            Tile::Purple => 'p',
+            Tile::IceCreamCone => 'I',
+            Tile::Gumdrop => 'U',
+            Tile::Lollipop => 'L',
+            Tile::Peppermint => 'E',
+            Tile::BonBon => 'N',
+            Tile::Start | Tile::End => return Err(DeckWriteError {position: i, card: c.clone()}),
+        };
+
+        if c.num > 2 {
+            return Err(DeckWriteError {position: i, card: c.clone()});
+        } else if c.num > 1 {
+            s.push(letter.to_ascii_uppercase());
+        } else {
+            s.push(letter);
+        }
+    }
+    Ok(s)
+}
+
+/// A deck in `write_deck` format, falling back to a debug listing for decks it can't encode
+pub(crate) fn deck_name(deck: &[Card]) -> String {
+    write_deck(deck).unwrap_or_else(|_| format!("{:?}", deck))
+}
+
+/// Read a deck back from the format produced by `write_deck`
+/// Whitespace is ignored, so decks can be wrapped across lines
+pub fn parse_deck(s: &str) -> Result<Vec<Card>, DeckParseError> {
+    let mut cards = Vec::new();
+    for (position, ch) in s.chars().enumerate() {
+        let (tile, num) = match ch {
+            'r' | 'R' => (Tile::Red, if ch == 'R' { 2 } else { 1 }),
+            'o' | 'O' => (Tile::Orange, if ch == 'O' { 2 } else { 1 }),
+            'y' | 'Y' => (Tile::Yellow, if ch == 'Y' { 2 } else { 1 }),
+            'g' | 'G' => (Tile::Green, if ch == 'G' { 2 } else { 1 }),
+            'b' | 'B' => (Tile::Blue, if ch == 'B' { 2 } else { 1 }),
+            'p' | 'P' => (Tile::Purple, if ch == 'P' { 2 } else { 1 }),
+            'I' => (Tile::IceCreamCone, 1),
+            'U' => (Tile::Gumdrop, 1),
+            'L' => (Tile::Lollipop, 1),
+            'E' => (Tile::Peppermint, 1),
+            'N' => (Tile::BonBon, 1),
+            _ if ch.is_whitespace() => continue,
+            _ => return Err(DeckParseError::InvalidCard {position, found: ch}),
         };
+        cards.push(Card {tile, num});
+    }
+    if cards.is_empty() {
+        return Err(DeckParseError::Empty);
+    }
+    Ok(cards)
+}
+
+/// Why a deck string couldn't be read by `parse_deck`
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeckParseError {
+    InvalidCard { position: usize, found: char }, // Position is the 0-based character index in the input
+    Empty,
+}
 
-        if c.num > 1 {
-            temp = temp.to_uppercase();
+impl std::fmt::Display for DeckParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DeckParseError::InvalidCard {position, found} => write!(f, "invalid card '{}' at position {}", found, position),
+            DeckParseError::Empty => write!(f, "deck has no cards"),
         }
-        s.push_str(&temp);
     }
-    return s;
 }
 
-fn print_summary(s: &Summary) {
-    let len = s.turns.len();
-    let mut avg_turns: f32 = 0.0;
-    for t in &s.turns {
-        avg_turns += *t as f32;
+impl std::error::Error for DeckParseError {}
+
+/// A card `write_deck` has no letter for, such as Start, End or a triple
+#[derive(Clone, Debug)]
+pub struct DeckWriteError {
+    pub position: usize, // Index of the offending card in the deck
+    pub card: Card,
+}
+
+impl std::fmt::Display for DeckWriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "card #{} ({} {:?}) has no letter", self.position, self.card.num, self.card.tile)
     }
-    avg_turns /= len as f32;
+}
+
+impl std::error::Error for DeckWriteError {}
 
-    let med: f32;
-    if len % 2 == 0 {
-        med = ((s.turns.get(len / 2).unwrap() + s.turns.get(len / 2 + 1).unwrap()) as f32) / 2.0;
+/// Print a human-readable report of the given results
+pub fn print_summary(s: &Summary) {
+    if let Some(t) = turn_stats(&s.turns) {
+        let p = &t.percentiles;
+        println!("Average # turns: {:.3}", t.mean);
+        println!("Median # turns: {:.1}", p.p50);
+        println!("Std dev: {:.3}, min: {}, max: {}", t.std_dev, t.min, t.max);
+        println!("Percentiles: p5 {:.1} / p25 {:.1} / p75 {:.1} / p95 {:.1} / p99 {:.1}", p.p5, p.p25, p.p75, p.p95, p.p99);
     }
-    else {
-        med = *s.turns.get(len / 2).unwrap() as f32;
+    println!("Reshuffles: {}", s.reshuffles);
+    if s.infinite + s.over_limit > 0 {
+        let unfinished = s.infinite + s.over_limit;
+        println!("Unfinished games: {} ({:.4}%): {} infinite, {} over the turn limit", unfinished,
+            unfinished as f64 * 100.0 / s.games() as f64, s.infinite, s.over_limit);
     }
-    println!("Average # turns: {}", avg_turns);
-    println!("Median # turns: {}", med);
-    let mut player = 1;
-    for w in s.winners.iter() {
-        println!("Player #{}: {}", player, w);
-        player += 1;
+    for (i, w) in win_rates(&s.winners, Z_95).iter().enumerate() {
+        println!("Player #{}: {} ({:.2}%, 95% CI {:.2}-{:.2}%)", i + 1, w.wins, w.rate * 100.0, w.low * 100.0, w.high * 100.0);
     }
-}
\ No newline at end of file
+}
+
+/// Print a bar chart of how many games took each number of turns
+pub fn print_histogram(s: &Summary) {
+    let Some(t) = turn_stats(&s.turns) else {
+        return;
+    };
+    let most = *t.histogram.iter().max().unwrap_or(&1);
+    for (turns, &count) in t.histogram.iter().enumerate().skip(t.min as usize) {
+        let bar = "#".repeat((count * 60).div_ceil(most) as usize);
+        println!("{:>4} {:>10} {}", turns, count, bar);
+    }
+    if t.max as usize >= HISTOGRAM_LIMIT {
+        println!("{:>3}+ {:>10}", HISTOGRAM_LIMIT, s.turns.sketch.buckets.values().sum::<u64>());
+    }
+}
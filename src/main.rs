@@ -1,9 +1,638 @@
+use candy_land::agents::*;
+use candy_land::analytics::*;
+use candy_land::board::*;
+use candy_land::checkpoint::*;
+use candy_land::deck::*;
+use candy_land::markov::*;
+use candy_land::rules::*;
+use candy_land::search::*;
+use candy_land::sensitivity::*;
+use candy_land::stats::*;
 use candy_land::*;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 
-const PLAYER_COUNT:PlayerCount = PlayerCount::Two;
-const NUM_GAMES: u32 = 10000000;
+#[derive(Parser)]
+#[command(about = "Simulate the game of Candy Land")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Play many games and report the results
+    Simulate {
+        #[command(flatten)]
+        table: Table,
+        /// Number of games to play
+        #[arg(short, long, default_value_t = 10000000)]
+        games: u32,
+        /// Master seed; a random one is picked (and reported) if omitted
+        #[arg(short, long)]
+        seed: Option<u64>,
+        /// Number of worker threads; defaults to every available core
+        #[arg(short, long)]
+        threads: Option<usize>,
+        /// Play every game with this fixed, never shuffled deck
+        #[arg(short, long)]
+        deck: Option<String>,
+        /// Write how often each space was landed on to this CSV file
+        #[arg(long)]
+        spaces: Option<PathBuf>,
+        /// Save the run's progress to this file as it goes, so it can be resumed if interrupted
+        #[arg(long)]
+        checkpoint: Option<PathBuf>,
+        /// Games between checkpoints
+        #[arg(long, default_value_t = 1000000, requires = "checkpoint")]
+        every: u32,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Carry on with a run from a checkpoint saved by `simulate --checkpoint`
+    Resume {
+        /// The checkpoint file, which keeps being updated as the run goes on
+        checkpoint: PathBuf,
+        /// Games between checkpoints
+        #[arg(long, default_value_t = 1000000)]
+        every: u32,
+        /// Number of worker threads; defaults to the number the run was started with
+        #[arg(short, long)]
+        threads: Option<usize>,
+        /// Write how often each space was landed on to this CSV file
+        #[arg(long)]
+        spaces: Option<PathBuf>,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Play a single game, either from a fixed deck or from a game of a seeded run
+    Replay {
+        #[command(flatten)]
+        table: Table,
+        /// The deck to play, in the letter format used when reporting infinite decks
+        #[arg(required_unless_present = "seed")]
+        deck: Option<String>,
+        /// Seed of the run the game came from
+        #[arg(short, long, conflicts_with = "deck")]
+        seed: Option<u64>,
+        /// Index of the game within the seeded run
+        #[arg(short, long, default_value_t = 0, requires = "seed")]
+        game: u64,
+        /// Write a turn-by-turn trace of the game as JSON Lines to this file ("-" for stdout)
+        #[arg(long)]
+        trace: Option<String>,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Search for decks that never finish without a reshuffle, and estimate how rare they are
+    Search {
+        #[command(flatten)]
+        table: Table,
+        /// Number of randomly shuffled decks to start from
+        #[arg(short, long, default_value_t = 10000)]
+        restarts: u64,
+        /// Card swaps to try from each starting deck
+        #[arg(long, default_value_t = 100)]
+        steps: u32,
+        /// Also search every larger player count up to this one
+        #[arg(long)]
+        up_to: Option<PlayerCount>,
+        /// Seed used to shuffle the decks; a random one is picked if omitted
+        #[arg(short, long)]
+        seed: Option<u64>,
+        /// Write every infinite deck found to this file, one per line
+        #[arg(long)]
+        out: Option<PathBuf>,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Show how a fixed deck's game changes when single cards are swapped or moved
+    Sensitivity {
+        #[command(flatten)]
+        table: Table,
+        /// The base deck, in the letter format used when reporting infinite decks
+        deck: String,
+        /// Seed for any random choices made during the games
+        #[arg(short, long, default_value_t = 0)]
+        seed: u64,
+        /// Number of most sensitive positions to list
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Compute the exact distribution of a single player's game length
+    /// Cards are drawn with replacement, so results differ slightly from a 1 player simulation
+    Exact {
+        /// Board file to analyze (.toml, .json or text); the standard board if omitted
+        #[arg(short, long)]
+        board: Option<PathBuf>,
+        /// Deck composition file (.toml or .json); the standard deck if omitted
+        #[arg(long)]
+        composition: Option<PathBuf>,
+        /// Report the probability of finishing within this many turns
+        #[arg(short, long, default_value_t = 20)]
+        turns: usize,
+        #[command(flatten)]
+        rules: RuleArgs,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Play the same games under every end rule and compare how long they take
+    Ends {
+        #[command(flatten)]
+        table: Table,
+        /// Number of games to play under each rule
+        #[arg(short, long, default_value_t = 1000000)]
+        games: u32,
+        /// Master seed; a random one is picked (and reported) if omitted
+        #[arg(short, long)]
+        seed: Option<u64>,
+        /// Number of worker threads; defaults to every available core
+        #[arg(short, long)]
+        threads: Option<usize>,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Test whether any seat at the table wins more often than the others
+    Seats {
+        #[command(flatten)]
+        table: Table,
+        /// Number of games to play for each player count
+        #[arg(short, long, default_value_t = 1000000)]
+        games: u32,
+        /// Also test every larger player count up to this one
+        #[arg(long)]
+        up_to: Option<PlayerCount>,
+        /// Win share above a fair one (0.01 is one percentage point) to work out the number of games needed to detect
+        #[arg(long, default_value_t = 0.01)]
+        edge: f64,
+        /// Master seed; a random one is picked (and reported) if omitted
+        #[arg(short, long)]
+        seed: Option<u64>,
+        /// Number of worker threads; defaults to every available core
+        #[arg(short, long)]
+        threads: Option<usize>,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Inspect and validate a board
+    Board {
+        /// Board file to check (.toml, .json or text); the standard board if omitted
+        file: Option<PathBuf>,
+        /// Deck composition file (.toml or .json) to check the board against; the standard deck if omitted
+        #[arg(long)]
+        composition: Option<PathBuf>,
+        #[command(flatten)]
+        output: Output,
+    },
+}
+
+/// The results of one end rule in a comparison
+#[derive(Serialize)]
+struct EndComparison {
+    end: EndRule,
+    summary: Summary,
+}
+
+#[derive(Args)]
+struct Table {
+    /// Number of players
+    #[arg(short, long, default_value = "2")]
+    players: PlayerCount,
+    /// Board file to play on (.toml, .json or text); the standard board if omitted
+    #[arg(short, long)]
+    board: Option<PathBuf>,
+    /// Deck composition file (.toml or .json) to shuffle decks from; the standard deck if omitted
+    #[arg(long)]
+    composition: Option<PathBuf>,
+    /// Give up on a game as unfinished once it has gone on this many turns
+    #[arg(long)]
+    max_turns: Option<u32>,
+    #[command(flatten)]
+    rules: RuleArgs,
+    /// Who makes each seat's optional choices, in seat order: always, never, greedy or random (the default)
+    #[arg(long, value_delimiter = ',')]
+    agents: Vec<AgentKind>,
+}
+
+#[derive(Args)]
+struct RuleArgs {
+    /// Let players share a space instead of skipping past occupied ones
+    #[arg(long)]
+    no_blocking: bool,
+    /// What landing on licorice does: lose-turn or until-color
+    #[arg(long, default_value = "lose-turn")]
+    licorice: Licorice,
+    /// Whether a player has to take a shortcut they land on: mandatory or optional
+    #[arg(long, default_value = "mandatory")]
+    shortcuts: Shortcuts,
+    /// Picture cards behind a player are lost instead of sending them back
+    #[arg(long)]
+    no_backward: bool,
+    /// How the End is reached: any-card, exact-color or rainbow
+    #[arg(long, default_value = "any-card")]
+    end: EndRule,
+}
+
+impl RuleArgs {
+    fn rules(&self) -> Rules {
+        Rules {blocking: !self.no_blocking, licorice: self.licorice, shortcuts: self.shortcuts, backward_pictures: !self.no_backward,
+            end: self.end}
+    }
+}
+
+impl Table {
+    /// Build a config for this table, exiting if the board can't be played with the deck
+    fn config(&self, games: u32, deck: Option<Vec<Card>>, seed: Option<u64>) -> Config {
+        let mut c = Config::new(self.players, games);
+        if let Some(path) = &self.board {
+            c.board = load_board(path);
+        }
+        if let Some(path) = &self.composition {
+            c.deck_spec = load_composition(path);
+        }
+        c.deck = deck;
+        c.max_turns = self.max_turns;
+        c.rules = self.rules.rules();
+        c.agents = self.agents.clone();
+        check_config(&c);
+        if let Some(seed) = seed {
+            c.seed = seed;
+        }
+        c
+    }
+}
+
+#[derive(Args)]
+struct Output {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+    /// Print more detail (may be repeated)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// Only print the results
+    #[arg(short, long, conflicts_with = "verbose")]
+    quiet: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+fn parse_deck_arg(s: &str) -> Vec<Card> {
+    parse_deck(s).unwrap_or_else(|e| {
+        eprintln!("Invalid deck: {}", e);
+        if let DeckParseError::InvalidCard {position, ..} = e {
+            eprintln!("  {}", s);
+            eprintln!("  {}^", " ".repeat(position));
+        }
+        std::process::exit(2);
+    })
+}
+
+fn load_board(path: &Path) -> BoardSpec {
+    BoardSpec::load(path).unwrap_or_else(|e| {
+        eprintln!("{}: {}", path.display(), e);
+        std::process::exit(2);
+    })
+}
+
+fn load_composition(path: &Path) -> DeckSpec {
+    DeckSpec::load(path).unwrap_or_else(|e| {
+        eprintln!("{}: {}", path.display(), e);
+        std::process::exit(2);
+    })
+}
+
+/// Exit if the config's games can't be played
+fn check_config(c: &Config) {
+    if let Err(e) = c.validate() {
+        eprintln!("Can't play this table: {}", e);
+        std::process::exit(2);
+    }
+}
+
+/// Play out the rest of a checkpointed run, saving to `path` every `every` games, and exit if saving fails
+fn run_checkpointed(mut cp: Checkpoint, path: &Path, every: u32, output: &Output) -> Summary {
+    let saved = cp.run(every, |cp| {
+        if output.verbose > 0 {
+            eprintln!("Saved checkpoint after {} of {} game(s)", cp.completed, cp.config.games);
+        }
+        cp.save(path)
+    });
+    if let Err(e) = saved {
+        eprintln!("{}: {}", path.display(), e);
+        std::process::exit(1);
+    }
+    cp.summary
+}
+
+/// Print the results of a run (and write its per-space counts, if asked to)
+fn report(c: &Config, s: &Summary, spaces: Option<&Path>, output: &Output) {
+    if let Some(path) = spaces {
+        if let Err(e) = std::fs::write(path, spaces_csv(&s.spaces, &c.board, s.turns.count)) {
+            eprintln!("Couldn't write {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    }
+    if output.format == Format::Json {
+        print_json(s);
+        return;
+    }
+    if !output.quiet {
+        println!("Done! Ran {} game(s) in {:.3} s (seed {})", s.games(), s.elapsed, s.seed);
+    }
+    if c.deck.is_some() || output.verbose > 0 {
+        for (d, n) in &s.unfinished_decks {
+            println!("Unfinished deck ({} game(s)): {}", n, d);
+        }
+        if !s.unfinished_games.is_empty() {
+            let games: Vec<String> = s.unfinished_games.iter().map(|g| g.to_string()).collect();
+            println!("Unfinished games (replay --seed {} --game N, with the same table options as this run): {}", s.seed, games.join(", "));
+        }
+    }
+    if s.games() > 0 {
+        print_summary(s);
+        if output.verbose > 0 && !s.turns.is_empty() {
+            print_histogram(s);
+            print_cards(&s.cards, s.turns.count);
+            print_seats(&s.seats, &s.winners, s.turns.count);
+            print_seat_advantage(&seat_advantage(&s.winners, 0.01));
+            print_heatmap(&s.spaces, &c.board, s.turns.count);
+        }
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) {
+    println!("{}", serde_json::to_string_pretty(value).unwrap());
+}
 
 fn main() {
-    calculate(PLAYER_COUNT, NUM_GAMES, None);
-    //calculate(PLAYER_COUNT, NUM_GAMES, Some(get_infinite_two_person_deck()));
-}
\ No newline at end of file
+    match Cli::parse().command {
+        Command::Simulate { table, games, seed, threads, deck, spaces, checkpoint, every, output } => {
+            let mut c = table.config(games, deck.as_deref().map(parse_deck_arg), seed);
+            if let Some(threads) = threads {
+                c.threads = threads;
+            }
+            if output.verbose > 0 {
+                eprintln!("Playing {} game(s) with {} players on {} thread(s) (seed {})", c.games, c.players, c.threads, c.seed);
+            }
+
+            let s = match &checkpoint {
+                Some(path) => run_checkpointed(Checkpoint::new(&c), path, every, &output),
+                None => simulate(&c),
+            };
+            report(&c, &s, spaces.as_deref(), &output);
+        }
+        Command::Resume { checkpoint, every, threads, spaces, output } => {
+            let mut cp = Checkpoint::load(&checkpoint).unwrap_or_else(|e| {
+                eprintln!("{}: {}", checkpoint.display(), e);
+                std::process::exit(2);
+            });
+            check_config(&cp.config);
+            if let Some(threads) = threads {
+                cp.config.threads = threads;
+            }
+            if output.verbose > 0 {
+                eprintln!("Resuming after {} of {} game(s) with {} players on {} thread(s) (seed {})", cp.completed, cp.config.games,
+                    cp.config.players, cp.config.threads, cp.config.seed);
+            }
+            let c = cp.config.clone();
+            let s = run_checkpointed(cp, &checkpoint, every, &output);
+            report(&c, &s, spaces.as_deref(), &output);
+        }
+        Command::Replay { table, deck, seed, game, trace, output } => {
+            let c = table.config(1, deck.as_deref().map(parse_deck_arg), Some(seed.unwrap_or(0)));
+            let (stats, t) = replay_traced(&c, game);
+            match trace.as_deref() {
+                Some("-") => {
+                    t.write_jsonl(std::io::stdout().lock()).unwrap();
+                    return; // Keep stdout valid JSON Lines
+                }
+                Some(path) => {
+                    let file = std::fs::File::create(path).unwrap_or_else(|e| {
+                        eprintln!("Couldn't create {}: {}", path, e);
+                        std::process::exit(1);
+                    });
+                    t.write_jsonl(std::io::BufWriter::new(file)).unwrap();
+                }
+                None => (),
+            }
+            if output.format == Format::Json {
+                print_json(&stats);
+            } else if let Some(cycle) = &stats.cycle {
+                println!("Deck was an infinite loop: from turn {} the game repeats every {} turns", cycle.start, cycle.length);
+            } else if stats.over_limit {
+                println!("Game was stopped unfinished after {} turns", stats.turns);
+            } else {
+                println!("Player #{} won after {} turns ({} cards drawn, {} reshuffles)", stats.winner, stats.turns, stats.cards_drawn, stats.reshuffles);
+                if output.verbose > 0 {
+                    print_cards(&stats.cards, 1);
+                    let wins: Vec<u32> = (1..=stats.seats.len() as u32).map(|p| (p == stats.winner) as u32).collect();
+                    print_seats(&stats.seats, &wins, 1);
+                }
+            }
+        }
+        Command::Search { table, restarts, steps, up_to, seed, out, output } => {
+            let mut c = table.config(0, None, seed);
+            let last = up_to.unwrap_or(c.players).value().max(c.players.value());
+            let mut reports = Vec::new();
+            for players in c.players.value()..=last {
+                c.players = PlayerCount::new(players).unwrap();
+                if output.verbose > 0 {
+                    eprintln!("Searching {} deck(s) for {} players (seed {})", restarts, c.players, c.seed);
+                }
+                reports.push(search(&c, restarts, steps));
+            }
+
+            if let Some(path) = &out {
+                let decks: Vec<&String> = reports.iter().flat_map(|r| &r.decks).collect();
+                let text: String = decks.iter().map(|d| format!("{}\n", d)).collect();
+                if let Err(e) = std::fs::write(path, text) {
+                    eprintln!("Couldn't write {}: {}", path.display(), e);
+                    std::process::exit(1);
+                }
+            }
+            if output.format == Format::Json {
+                print_json(&reports);
+                return;
+            }
+            for r in &reports {
+                if out.is_none() || output.verbose > 0 {
+                    for d in &r.decks {
+                        println!("{}", d);
+                    }
+                }
+                if output.quiet {
+                    println!("{} players: {:.4}% ({:.4}-{:.4}%)", r.players, r.rate * 100.0, r.low * 100.0, r.high * 100.0);
+                } else {
+                    println!("{} players: {} of {} random deck(s) infinite ({:.4}%, 95% CI {:.4}-{:.4}%), {} unique infinite deck(s) in {} games",
+                        r.players, r.random_hits, r.restarts, r.rate * 100.0, r.low * 100.0, r.high * 100.0, r.decks.len(), r.evaluations);
+                }
+            }
+        }
+        Command::Sensitivity { table, deck, seed, top, output } => {
+            let c = table.config(1, Some(parse_deck_arg(&deck)), Some(seed));
+            let s = sensitivity(&c).unwrap();
+            if output.format == Format::Json {
+                print_json(&s);
+                return;
+            }
+            match &s.base.cycle {
+                Some(cycle) => println!("Base deck loops forever: from turn {} the game repeats every {} turns", cycle.start, cycle.length),
+                None if s.base.infinite => println!("Base deck loops forever"),
+                None => println!("Base deck: player #{} wins after {} turns", s.base.winner, s.base.turns),
+            }
+            let looped = if s.base.infinite { "Loop broken" } else { "Loop made" };
+            for (name, e) in [("Swaps", &s.swaps), ("Moves", &s.moves)] {
+                print!("{}: {} deck(s), winner changed in {} ({:.2}%), {} in {} ({:.2}%)", name, e.variants,
+                    e.winner_changed, e.winner_changed as f64 * 100.0 / e.variants.max(1) as f64, looped.to_lowercase(),
+                    e.loop_changed, e.loop_changed as f64 * 100.0 / e.variants.max(1) as f64);
+                if s.base.infinite {
+                    println!();
+                } else {
+                    println!(", mean length change {:.2} turns", e.mean_turn_change());
+                }
+            }
+
+            let mut positions: Vec<&PositionEffect> = s.positions.iter().collect();
+            positions.sort_by_key(|p| std::cmp::Reverse((p.effect.loop_changed, p.effect.winner_changed)));
+            if !output.quiet {
+                println!("Most sensitive positions (0-based, in drawing order):");
+            }
+            println!("{:>4} {:<14} {:>9} {:>15} {:>12} {:>13}", "Pos", "Card", "Variants", "Winner changed", looped, "Mean change");
+            for p in positions.iter().take(top) {
+                let e = &p.effect;
+                let change = if s.base.infinite { "-".to_string() } else { format!("{:.2}", e.mean_turn_change()) };
+                println!("{:>4} {:<14} {:>9} {:>15} {:>12} {:>13}", p.position, card_name(&p.card), e.variants,
+                    e.winner_changed, e.loop_changed, change);
+            }
+        }
+        Command::Exact { board, composition, turns, rules, output } => {
+            let board = board.as_deref().map(load_board).unwrap_or_default();
+            let deck = composition.as_deref().map(load_composition).unwrap_or_default();
+            if let Err(e) = deck.validate(&board) {
+                eprintln!("Invalid deck composition: {}", e);
+                std::process::exit(2);
+            }
+            if let Err(e) = board.validate(&deck.build()) {
+                eprintln!("Invalid board: {}", e);
+                std::process::exit(2);
+            }
+            let rules = rules.rules();
+            if let Err(e) = rules.validate(&board, &deck.build()) {
+                eprintln!("Invalid rules: {}", e);
+                std::process::exit(2);
+            }
+            let Some(a) = analyze(&board, &deck, &rules) else {
+                eprintln!("Some reachable position can never finish");
+                std::process::exit(1);
+            };
+
+            if output.format == Format::Json {
+                print_json(&a);
+                return;
+            }
+            println!("Mean # turns: {:.6}", a.mean);
+            println!("Std dev: {:.6}", a.std_dev());
+            println!("P(finish within {} turns): {:.6}", turns, a.finished_within(turns));
+            if output.verbose > 0 {
+                let mut total = 0.0;
+                for (t, p) in a.distribution.iter().enumerate().skip(1) {
+                    total += p;
+                    println!("{:>4} {:.8} {:.8}", t, p, total);
+                }
+            }
+        }
+        Command::Ends { table, games, seed, threads, output } => {
+            let mut c = table.config(games, None, seed);
+            if let Some(threads) = threads {
+                c.threads = threads;
+            }
+            let mut results = Vec::new();
+            for end in EndRule::ALL {
+                c.rules.end = end;
+                check_config(&c);
+                if output.verbose > 0 {
+                    eprintln!("Playing {} game(s) with {} players under the {} end rule (seed {})", c.games, c.players, end, c.seed);
+                }
+                results.push(EndComparison {end, summary: simulate(&c)});
+            }
+
+            if output.format == Format::Json {
+                print_json(&results);
+                return;
+            }
+            if !output.quiet {
+                println!("Ran {} game(s) per end rule (seed {})", c.games, c.seed);
+            }
+            println!("{:<12} {:>9} {:>7} {:>8} {:>5} {:>5} {:>6} {:>11} {:>9}", "End rule", "Mean", "Median", "Std dev", "p95", "Max", "Cards", "Unfinished", "Seat #1");
+            for r in &results {
+                let s = &r.summary;
+                let Some(t) = turn_stats(&s.turns) else {
+                    println!("{:<12} no finished games", r.end.to_string());
+                    continue;
+                };
+                let cards = s.cards.drawn.total() as f64 / s.turns.count as f64;
+                let first = win_rates(&s.winners, Z_95)[0].rate;
+                println!("{:<12} {:>9.3} {:>7.1} {:>8.3} {:>5.1} {:>5} {:>6.2} {:>11} {:>8.2}%", r.end.to_string(), t.mean, t.percentiles.p50,
+                    t.std_dev, t.percentiles.p95, t.max, cards, s.infinite + s.over_limit, first * 100.0);
+            }
+        }
+        Command::Seats { table, games, up_to, edge, seed, threads, output } => {
+            let mut c = table.config(games, None, seed);
+            if let Some(threads) = threads {
+                c.threads = threads;
+            }
+            let last = up_to.unwrap_or(c.players).value().max(c.players.value());
+            let mut reports = Vec::new();
+            for players in c.players.value()..=last {
+                c.players = PlayerCount::new(players).unwrap();
+                if output.verbose > 0 {
+                    eprintln!("Playing {} game(s) with {} players (seed {})", c.games, c.players, c.seed);
+                }
+                reports.push(seat_advantage(&simulate(&c).winners, edge));
+            }
+
+            if output.format == Format::Json {
+                print_json(&reports);
+                return;
+            }
+            for (i, a) in reports.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                if !output.quiet {
+                    println!("{} players, {} game(s) (seed {})", a.seats.len(), c.games, c.seed);
+                }
+                print_seat_advantage(a);
+            }
+        }
+        Command::Board { file, composition, output } => {
+            let spec = match &file {
+                Some(path) => load_board(path),
+                None => BoardSpec::default(),
+            };
+            let deck = composition.as_deref().map(load_composition).unwrap_or_default();
+            let check = deck.validate(&spec).map_err(|e| e.to_string())
+                .and_then(|_| spec.validate(&deck.build()).map_err(|e| e.to_string()));
+            if output.format == Format::Json {
+                print_json(&spec);
+            } else if !output.quiet {
+                print!("{}", spec.to_text());
+            }
+            match check {
+                Ok(()) => if output.format == Format::Text && !output.quiet { eprintln!("Board is valid ({} spaces)", spec.tiles.len()) },
+                Err(e) => {
+                    eprintln!("Board is invalid: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use crate::*;
+
+/// Stop extending the turn distribution once less than this much probability is left unfinished
+const TAIL_TOLERANCE: f64 = 1e-12;
+
+/// Safety cap on the length of the turn distribution
+const MAX_TURNS: usize = 100000;
+
+/// The exact distribution of how long a single player takes to finish
+/// Cards are assumed to be drawn with replacement (as if the deck were reshuffled before every draw),
+/// which turns the game into a Markov chain over (space, stuck on licorice) states
+/// Optional shortcuts are taken half the time, and blocking never comes up with a single player
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Analysis {
+    pub distribution: Vec<f64>, // Probability of finishing on exactly turn #i (index 0 is always 0)
+    pub tail: f64, // Probability left over past the end of `distribution`
+    pub mean: f64,
+    pub variance: f64,
+}
+
+impl Analysis {
+    /// Probability of having finished within the given number of turns
+    pub fn finished_within(&self, turns: usize) -> f64 {
+        self.distribution.iter().take(turns + 1).sum()
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance.sqrt()
+    }
+}
+
+/// State #i is the player standing on space i/2, stuck on licorice if i is odd
+fn state(space: usize, stuck: bool) -> usize {
+    space * 2 + stuck as usize
+}
+
+/// The transitions between every state reachable from the given starting states
+struct Chain {
+    transitions: Vec<Vec<(usize, f64)>>, // Where each state leads after one turn, and how likely that is
+    transient: Vec<usize>, // Reachable states that haven't finished yet
+    index: Vec<usize>, // Position of each state in `transient`, or usize::MAX
+    q: Vec<Vec<f64>>, // Transition probabilities between the transient states
+}
+
+impl Chain {
+    fn build(spaces: &[Space], deck: &DeckSpec, rules: &Rules, starts: &[usize]) -> Chain {
+        let end = spaces.len() - 1;
+        let total = deck.len() as f64;
+
+        // Work out where each card leads from every reachable state
+        let mut transitions: Vec<Vec<(usize, f64)>> = vec![Vec::new(); spaces.len() * 2];
+        let mut reachable = vec![false; spaces.len() * 2];
+        let mut queue = starts.to_vec();
+        for &s in starts {
+            reachable[s] = true;
+        }
+        while let Some(from) = queue.pop() {
+            let (space, stuck) = (from / 2, from % 2 == 1);
+            if space == end {
+                continue;
+            }
+            if stuck && rules.licorice == Licorice::LoseTurn {
+                transitions[from].push((state(space, false), 1.0));
+            } else {
+                for e in deck.cards.iter().filter(|e| e.count > 0) {
+                    let p = e.count as f64 / total;
+                    if stuck && e.tile != spaces[space].tile {
+                        transitions[from].push((from, p)); // Still waiting for the licorice's color
+                        continue;
+                    }
+                    let card = Card {tile: e.tile.clone(), num: e.num};
+                    let landed = match landing_space(spaces, space, &card, rules.end, |_| false) {
+                        Some(l) if l >= space || rules.backward_pictures => l,
+                        _ => {
+                            transitions[from].push((state(space, false), p)); // The card was lost
+                            continue;
+                        }
+                    };
+                    let sticky = spaces[landed].sticky;
+                    match (spaces[landed].shortcut, rules.shortcuts) {
+                        (Some(to), Shortcuts::Mandatory) => transitions[from].push((state(to, sticky), p)),
+                        (Some(to), Shortcuts::Optional) => {
+                            transitions[from].push((state(to, sticky), p / 2.0));
+                            transitions[from].push((state(landed, sticky), p / 2.0));
+                        }
+                        (None, _) => transitions[from].push((state(landed, sticky), p)),
+                    }
+                }
+            }
+            for &(to, _) in &transitions[from] {
+                if !reachable[to] {
+                    reachable[to] = true;
+                    queue.push(to);
+                }
+            }
+        }
+
+        // Number the transient (unfinished) states
+        let transient: Vec<usize> = (0..reachable.len()).filter(|&i| reachable[i] && i / 2 != end).collect();
+        let mut index = vec![usize::MAX; reachable.len()];
+        for (n, &i) in transient.iter().enumerate() {
+            index[i] = n;
+        }
+        let n = transient.len();
+        let mut q = vec![vec![0.0; n]; n];
+        for (row, &i) in transient.iter().enumerate() {
+            for &(to, p) in &transitions[i] {
+                if index[to] != usize::MAX {
+                    q[row][index[to]] += p;
+                }
+            }
+        }
+        Chain {transitions, transient, index, q}
+    }
+
+    /// I - Q
+    fn fundamental(&self) -> Vec<Vec<f64>> {
+        self.q.iter().enumerate()
+            .map(|(r, row)| row.iter().enumerate().map(|(c, &p)| if r == c { 1.0 - p } else { -p }).collect())
+            .collect()
+    }
+
+    /// Expected number of turns left to finish from each transient state, or None if some can never finish
+    fn expected_turns(&self) -> Option<Vec<f64>> {
+        solve(&mut self.fundamental(), vec![1.0; self.transient.len()])
+    }
+}
+
+/// Build the transition matrix for the given board, deck and rules, and solve it
+/// Returns None if some position the player can reach has no way of ever finishing
+pub fn analyze(board: &BoardSpec, deck: &DeckSpec, rules: &Rules) -> Option<Analysis> {
+    let spaces = board.build();
+    let chain = Chain::build(&spaces, deck, rules, &[state(0, false)]);
+    let Chain {transitions, transient, index, q} = &chain;
+    let n = transient.len();
+
+    // Solve for the expected number of turns (and its second moment) from each transient state:
+    // (I - Q)m = 1 and (I - Q)s = 1 + 2Qm
+    let m = chain.expected_turns()?;
+    let rhs = (0..n).map(|r| 1.0 + 2.0 * (0..n).map(|c| q[r][c] * m[c]).sum::<f64>()).collect();
+    let second = solve(&mut chain.fundamental(), rhs)?;
+    let start = index[state(0, false)];
+
+    // Push the probability of standing on each state forward one turn at a time
+    let mut dist = vec![0.0];
+    let mut v = vec![0.0; n];
+    v[start] = 1.0;
+    let mut tail = 1.0;
+    while tail > TAIL_TOLERANCE && dist.len() <= MAX_TURNS {
+        let mut next = vec![0.0; n];
+        let mut finished = 0.0;
+        for (row, &i) in transient.iter().enumerate() {
+            if v[row] == 0.0 {
+                continue;
+            }
+            for &(to, p) in &transitions[i] {
+                match index[to] {
+                    usize::MAX => finished += v[row] * p,
+                    col => next[col] += v[row] * p,
+                }
+            }
+        }
+        dist.push(finished);
+        v = next;
+        tail = v.iter().sum();
+    }
+
+    Some(Analysis {distribution: dist, tail, mean: m[start], variance: second[start] - m[start] * m[start]})
+}
+
+/// Expected number of turns a lone player still needs to finish from every space, and from every space while
+/// stuck on licorice; 0 on the End
+/// Shortcuts are taken whenever they're landed on. Returns None if some position can never finish
+pub fn turns_to_finish(board: &BoardSpec, deck: &DeckSpec, rules: &Rules) -> Option<Vec<[f64; 2]>> {
+    let spaces = board.build();
+    let rules = Rules {shortcuts: Shortcuts::Mandatory, ..rules.clone()};
+    let starts: Vec<usize> = (0..spaces.len() * 2).collect();
+    let chain = Chain::build(&spaces, deck, &rules, &starts);
+    let m = chain.expected_turns()?;
+    let turns = |s: usize| chain.index.get(s).and_then(|&i| m.get(i)).copied().unwrap_or(0.0);
+    Some((0..spaces.len()).map(|space| [turns(state(space, false)), turns(state(space, true))]).collect())
+}
+
+/// Solve the linear system `a`x = `b` by Gaussian elimination, or None if `a` is singular
+fn solve(a: &mut [Vec<f64>], mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&x, &y| a[x][col].abs().total_cmp(&a[y][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let (top, rest) = a.split_at_mut(col + 1);
+        let pivot_row = &top[col];
+        for (offset, row) in rest.iter_mut().enumerate() {
+            let f = row[col] / pivot_row[col];
+            if f != 0.0 {
+                for (x, p) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                    *x -= f * p;
+                }
+                b[col + 1 + offset] -= f * b[col];
+            }
+        }
+    }
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
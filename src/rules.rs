@@ -0,0 +1,122 @@
+use crate::*;
+
+/// The house rules a game is played by
+/// The default is the interpretation the simulator has always used
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    pub blocking: bool, // A color card skips past spaces another player is standing on
+    pub licorice: Licorice,
+    pub shortcuts: Shortcuts,
+    pub backward_pictures: bool, // Picture cards behind a player still move them; otherwise the card is lost
+    pub end: EndRule,
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules {blocking: true, licorice: Licorice::LoseTurn, shortcuts: Shortcuts::Mandatory, backward_pictures: true,
+            end: EndRule::AnyCard}
+    }
+}
+
+/// What happens to a player who lands on licorice
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Licorice {
+    LoseTurn, // Skip the next turn
+    UntilColor, // Stay put until drawing a card of the licorice space's color, which is then played
+}
+
+/// Whether a player landing on a shortcut has to take it
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Shortcuts {
+    Mandatory,
+    Optional, // The seat's agent decides
+}
+
+/// How a player gets from the last spaces onto the End
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EndRule {
+    AnyCard, // Any color card with no match left ahead goes to the End
+    ExactColor, // Only a card of the last colored space's color can go past it to the End; others are lost
+    Rainbow, // The End matches every color, so it has to be reached exactly; overshooting it loses the card
+}
+
+impl EndRule {
+    pub const ALL: [EndRule; 3] = [EndRule::AnyCard, EndRule::ExactColor, EndRule::Rainbow];
+}
+
+impl std::fmt::Display for EndRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            EndRule::AnyCard => write!(f, "any-card"),
+            EndRule::ExactColor => write!(f, "exact-color"),
+            EndRule::Rainbow => write!(f, "rainbow"),
+        }
+    }
+}
+
+impl std::str::FromStr for EndRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<EndRule, String> {
+        EndRule::ALL.into_iter().find(|e| e.to_string() == s)
+            .ok_or_else(|| format!("'{}' isn't an end rule (any-card, exact-color or rainbow)", s))
+    }
+}
+
+impl std::str::FromStr for Licorice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Licorice, String> {
+        match s {
+            "lose-turn" => Ok(Licorice::LoseTurn),
+            "until-color" => Ok(Licorice::UntilColor),
+            _ => Err(format!("'{}' isn't a licorice rule (lose-turn or until-color)", s)),
+        }
+    }
+}
+
+impl std::str::FromStr for Shortcuts {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Shortcuts, String> {
+        match s {
+            "mandatory" => Ok(Shortcuts::Mandatory),
+            "optional" => Ok(Shortcuts::Optional),
+            _ => Err(format!("'{}' isn't a shortcut rule (mandatory or optional)", s)),
+        }
+    }
+}
+
+/// The End can't be reached under `EndRule::ExactColor`: the deck has no card of the last colored space's color
+/// (or the board has no colored spaces at all)
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnreachableEnd(pub Option<Tile>);
+
+impl std::fmt::Display for UnreachableEnd {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.0 {
+            Some(tile) => write!(f, "the End needs a {:?} card, but the deck has none", tile),
+            None => write!(f, "the End needs a card of the last colored space's color, but the board has none"),
+        }
+    }
+}
+
+impl std::error::Error for UnreachableEnd {}
+
+impl Rules {
+    /// Check that a player can still finish on the given board with the given deck under these rules
+    pub fn validate(&self, board: &BoardSpec, deck: &[Card]) -> Result<(), UnreachableEnd> {
+        if self.end != EndRule::ExactColor {
+            return Ok(());
+        }
+        let color = final_color(&board.build());
+        match &color {
+            Some(tile) if deck.iter().any(|c| &c.tile == tile) => Ok(()),
+            _ => Err(UnreachableEnd(color)),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::*;
+use std::collections::BTreeSet;
+
+/// What a search for infinite decks turned up
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SearchReport {
+    pub players: PlayerCount,
+    pub restarts: u64, // Randomly shuffled decks the search started from
+    pub random_hits: u64, // How many of those starting decks were already infinite
+    pub rate: f64, // Share of randomly shuffled decks that are infinite
+    pub low: f64, // 95% confidence interval on `rate`
+    pub high: f64,
+    pub evaluations: u64, // Games played in total, including every step of the local search
+    pub decks: BTreeSet<String>, // Every unique infinite deck found, in `write_deck` format
+}
+
+/// Hunt for decks that loop forever without a reshuffle, for the config's players, board and deck composition
+/// Each restart shuffles a fresh deck, then hill-climbs from it for the given number of steps, swapping two
+/// cards at a time and keeping the swap if the game got no shorter. Infinite decks score highest, so once one
+/// is found the search wanders between its infinite neighbours, collecting them as it goes
+/// Restart #i uses `game_rng(seed, i)`, so a search can be repeated exactly
+pub fn search(c: &Config, restarts: u64, steps: u32) -> SearchReport {
+    let setup = Setup::new(c);
+    let score = |deck: &[Card], rng: &mut ChaCha8Rng| {
+        let stats = play(c, &setup, Some(deck.to_vec()), rng, None);
+        if stats.infinite { u32::MAX } else { stats.turns }
+    };
+
+    let mut decks = BTreeSet::new();
+    let mut random_hits = 0;
+    let mut evaluations = 0;
+    for i in 0..restarts {
+        let mut rng = game_rng(c.seed, i);
+        let mut deck = c.deck_spec.build();
+        deck.shuffle(&mut rng);
+        let mut best = score(&deck, &mut rng);
+        evaluations += 1;
+        if best == u32::MAX {
+            random_hits += 1;
+            decks.insert(deck_name(&deck));
+        }
+
+        for _ in 0..steps {
+            let (a, b) = (rng.gen_range(0..deck.len()), rng.gen_range(0..deck.len()));
+            if deck[a] == deck[b] {
+                continue;
+            }
+            deck.swap(a, b);
+            let s = score(&deck, &mut rng);
+            evaluations += 1;
+            if s == u32::MAX {
+                decks.insert(deck_name(&deck));
+            }
+            if s >= best {
+                best = s;
+            } else {
+                deck.swap(a, b);
+            }
+        }
+    }
+
+    let (low, high) = wilson_interval(random_hits, restarts, Z_95);
+    SearchReport {
+        players: c.players,
+        restarts,
+        random_hits,
+        rate: random_hits as f64 / restarts.max(1) as f64,
+        low,
+        high,
+        evaluations,
+        decks,
+    }
+}
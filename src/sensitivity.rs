@@ -0,0 +1,96 @@
+use crate::*;
+
+/// How a game played from one particular deck order went
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Outcome {
+    pub winner: u32, // 0 if the game didn't finish
+    pub turns: u32,
+    pub infinite: bool,
+    pub cycle: Option<Cycle>,
+}
+
+/// How much a set of changes to the base deck changed the game
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Effect {
+    pub variants: u64, // Decks played
+    pub winner_changed: u64, // ...where someone else won (or the game no longer finished, or now did)
+    pub loop_changed: u64, // ...where an infinite base deck finished, or a finite one started looping
+    pub turn_change: u64, // Total difference in length, over variants that finished as well as the base deck
+    pub finished: u64, // Variants counted in `turn_change`
+}
+
+impl Effect {
+    fn add(&mut self, base: &Outcome, variant: &Outcome) {
+        self.variants += 1;
+        self.winner_changed += (variant.winner != base.winner) as u64;
+        self.loop_changed += (variant.infinite != base.infinite) as u64;
+        if !base.infinite && !variant.infinite {
+            self.turn_change += base.turns.abs_diff(variant.turns) as u64;
+            self.finished += 1;
+        }
+    }
+
+    /// Average difference in length from the base deck, where both finished
+    pub fn mean_turn_change(&self) -> f64 {
+        self.turn_change as f64 / self.finished.max(1) as f64
+    }
+}
+
+/// What changing one position of the base deck did
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PositionEffect {
+    pub position: usize, // 0-based, in drawing order
+    pub card: Card,
+    pub effect: Effect, // Every swap involving this position, and every move of its card
+}
+
+/// How a fixed deck's game depends on the order of its cards
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Sensitivity {
+    pub base: Outcome,
+    pub swaps: Effect, // Every pair of cards swapped
+    pub moves: Effect, // Every card taken out and put back at every other position, where that isn't one of the swaps
+    pub positions: Vec<PositionEffect>,
+}
+
+/// Play the config's fixed deck, then every deck one swap or one move away from it, and compare
+/// Every game is played with `game_rng(seed, 0)`, so only the deck order differs between them
+pub fn sensitivity(c: &Config) -> Option<Sensitivity> {
+    let base_deck = c.deck.clone()?;
+    let setup = Setup::new(c);
+    let outcome = |deck: Vec<Card>| {
+        let stats = play(c, &setup, Some(deck), &mut game_rng(c.seed, 0), None);
+        Outcome {winner: stats.winner, turns: stats.turns, infinite: stats.infinite, cycle: stats.cycle}
+    };
+    let base = outcome(base_deck.clone());
+
+    let mut positions: Vec<PositionEffect> = base_deck.iter().enumerate()
+        .map(|(position, card)| PositionEffect {position, card: card.clone(), effect: Effect::default()})
+        .collect();
+    let mut swaps = Effect::default();
+    let mut moves = Effect::default();
+    for i in 0..base_deck.len() {
+        for j in 0..base_deck.len() {
+            // Take card #i out and put it back in at #j, unless that's the base deck or a swap with a neighbour
+            let mut deck = base_deck.clone();
+            let card = deck.remove(i);
+            deck.insert(j, card);
+            if i.abs_diff(j) > 1 && deck != base_deck {
+                let o = outcome(deck);
+                moves.add(&base, &o);
+                positions[i].effect.add(&base, &o);
+            }
+
+            // Swap cards #i and #j, unless they're the same kind of card
+            if i < j && base_deck[i] != base_deck[j] {
+                let mut deck = base_deck.clone();
+                deck.swap(i, j);
+                let o = outcome(deck);
+                swaps.add(&base, &o);
+                positions[i].effect.add(&base, &o);
+                positions[j].effect.add(&base, &o);
+            }
+        }
+    }
+    Some(Sensitivity {base, swaps, moves, positions})
+}
@@ -0,0 +1,317 @@
+use crate::*;
+
+/// z-score for a 95% confidence interval
+pub const Z_95: f64 = 1.959964;
+
+/// Distribution of the number of turns games took
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TurnStats {
+    pub games: u64,
+    pub min: u32,
+    pub max: u32,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub percentiles: Percentiles,
+    pub histogram: Vec<u64>, // Number of games that took exactly i turns, up to HISTOGRAM_LIMIT
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Percentiles {
+    pub p5: f64,
+    pub p25: f64,
+    pub p50: f64,
+    pub p75: f64,
+    pub p95: f64,
+    pub p99: f64,
+}
+
+/// How often one player won, with a confidence interval on their true win rate
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WinRate {
+    pub wins: u32,
+    pub rate: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+/// Turn counts below this are counted exactly; longer games go to the quantile sketch
+pub const HISTOGRAM_LIMIT: usize = 1024;
+
+/// Relative accuracy of quantiles that fall in the sketch
+pub const SKETCH_ACCURACY: f64 = 0.01;
+
+/// Constant-memory running statistics of game lengths
+/// Everything is kept as integer counts, so merging is exact and the order games are added in doesn't matter
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TurnAccumulator {
+    pub count: u64,
+    pub sum: u64,
+    pub sum_sq: u128,
+    pub min: u32,
+    pub max: u32,
+    pub histogram: Vec<u64>, // Number of games that took exactly i turns, for i < HISTOGRAM_LIMIT
+    pub sketch: QuantileSketch, // Games of HISTOGRAM_LIMIT turns or more
+}
+
+impl TurnAccumulator {
+    pub fn add(&mut self, turns: u32) {
+        if self.count == 0 || turns < self.min {
+            self.min = turns;
+        }
+        self.max = self.max.max(turns);
+        self.count += 1;
+        self.sum += turns as u64;
+        self.sum_sq += (turns as u128) * (turns as u128);
+        if (turns as usize) < HISTOGRAM_LIMIT {
+            if self.histogram.len() <= turns as usize {
+                self.histogram.resize(turns as usize + 1, 0);
+            }
+            self.histogram[turns as usize] += 1;
+        } else {
+            self.sketch.add(turns);
+        }
+    }
+
+    pub fn merge(&mut self, other: &TurnAccumulator) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 || other.min < self.min {
+            self.min = other.min;
+        }
+        self.max = self.max.max(other.max);
+        self.count += other.count;
+        self.sum += other.sum;
+        self.sum_sq += other.sum_sq;
+        if self.histogram.len() < other.histogram.len() {
+            self.histogram.resize(other.histogram.len(), 0);
+        }
+        for (h, o) in self.histogram.iter_mut().zip(&other.histogram) {
+            *h += o;
+        }
+        self.sketch.merge(&other.sketch);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.sum as f64 / self.count as f64
+    }
+
+    /// Sample variance
+    pub fn variance(&self) -> f64 {
+        if self.count < 2 {
+            return 0.0;
+        }
+        let n = self.count as f64;
+        (self.sum_sq as f64 - (self.sum as f64) * (self.sum as f64) / n) / (n - 1.0)
+    }
+
+    /// The `q`th quantile (0 to 1), interpolating between neighbouring games
+    /// The median of an even number of games is the average of the middle two
+    /// Exact unless it falls among games longer than HISTOGRAM_LIMIT turns
+    pub fn quantile(&self, q: f64) -> f64 {
+        let pos = q.clamp(0.0, 1.0) * (self.count - 1) as f64;
+        let (lo, hi) = (self.nth(pos.floor() as u64), self.nth(pos.ceil() as u64));
+        lo + (hi - lo) * (pos - pos.floor())
+    }
+
+    /// Turns taken by the nth shortest game (0-based)
+    fn nth(&self, n: u64) -> f64 {
+        let mut seen = 0;
+        for (turns, &c) in self.histogram.iter().enumerate() {
+            seen += c;
+            if seen > n {
+                return turns as f64;
+            }
+        }
+        self.sketch.nth(n - seen).min(self.max as f64)
+    }
+}
+
+/// A mergeable sketch of values, keeping counts in logarithmically sized buckets
+/// Any value it reports is within SKETCH_ACCURACY of a value that was added
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct QuantileSketch {
+    pub buckets: std::collections::BTreeMap<i32, u64>,
+}
+
+impl QuantileSketch {
+    fn gamma() -> f64 {
+        (1.0 + SKETCH_ACCURACY) / (1.0 - SKETCH_ACCURACY)
+    }
+
+    pub fn add(&mut self, value: u32) {
+        let key = (value.max(1) as f64).ln() / QuantileSketch::gamma().ln();
+        *self.buckets.entry(key.ceil() as i32).or_insert(0) += 1;
+    }
+
+    pub fn merge(&mut self, other: &QuantileSketch) {
+        for (k, c) in &other.buckets {
+            *self.buckets.entry(*k).or_insert(0) += c;
+        }
+    }
+
+    /// Estimate of the nth smallest value (0-based)
+    pub fn nth(&self, n: u64) -> f64 {
+        let gamma = QuantileSketch::gamma();
+        let mut seen = 0;
+        for (&k, &c) in &self.buckets {
+            seen += c;
+            if seen > n {
+                return 2.0 * gamma.powi(k) / (gamma + 1.0);
+            }
+        }
+        f64::NAN
+    }
+}
+
+/// Work out the turn statistics of the given games, or None if there are none
+pub fn turn_stats(turns: &TurnAccumulator) -> Option<TurnStats> {
+    if turns.is_empty() {
+        return None;
+    }
+    let p = |q| turns.quantile(q);
+    Some(TurnStats {
+        games: turns.count,
+        min: turns.min,
+        max: turns.max,
+        mean: turns.mean(),
+        std_dev: turns.variance().sqrt(),
+        percentiles: Percentiles {p5: p(0.05), p25: p(0.25), p50: p(0.5), p75: p(0.75), p95: p(0.95), p99: p(0.99)},
+        histogram: turns.histogram.clone(),
+    })
+}
+
+/// Each player's share of the wins, with Wilson score intervals at the given z-score
+pub fn win_rates(winners: &[u32], z: f64) -> Vec<WinRate> {
+    let total: u32 = winners.iter().sum();
+    winners.iter().map(|&wins| {
+        let (low, high) = wilson_interval(wins as u64, total as u64, z);
+        WinRate {wins, rate: wins as f64 / total.max(1) as f64, low, high}
+    }).collect()
+}
+
+/// Wilson score interval for `successes` out of `trials`
+pub fn wilson_interval(successes: u64, trials: u64, z: f64) -> (f64, f64) {
+    if trials == 0 {
+        return (0.0, 1.0);
+    }
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let z2 = z * z;
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let half = z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+    ((center - half).max(0.0), (center + half).min(1.0))
+}
+
+/// z-score for 80% power in a sample size calculation
+pub const Z_POWER_80: f64 = 0.841621;
+
+/// Whether some seats at the table win more often than others
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SeatAdvantage {
+    pub seats: Vec<WinRate>, // Each seat's share of the wins, with a 95% confidence interval
+    pub chi_square: f64, // Pearson's statistic against every seat winning equally often
+    pub degrees: u32, // Degrees of freedom of the test (seats - 1)
+    pub p_value: f64, // Chance of a statistic at least this large if no seat has an advantage
+    pub edge: f64, // Advantage, in absolute win share over a fair 1/seats, that `games_needed` is for
+    pub games_needed: Option<u64>, // Games to detect that edge for one seat (5% significance, 80% power); None for solitaire
+}
+
+/// Test the given win counts for a seat advantage, and work out how many games it would take to detect
+/// one seat winning `edge` more often than its fair share
+pub fn seat_advantage(winners: &[u32], edge: f64) -> SeatAdvantage {
+    let total: u64 = winners.iter().map(|&w| w as u64).sum();
+    let k = winners.len();
+    let expected = total as f64 / k as f64;
+    let chi_square = if total == 0 || k < 2 {
+        0.0
+    } else {
+        winners.iter().map(|&w| (w as f64 - expected).powi(2) / expected).sum()
+    };
+    let degrees = k.saturating_sub(1) as u32;
+    let p_value = if degrees == 0 { 1.0 } else { gamma_q(degrees as f64 / 2.0, chi_square / 2.0) };
+
+    // One-sample test of a proportion: n = ((z_a sqrt(p0 q0) + z_b sqrt(p1 q1)) / edge)^2
+    let p0 = 1.0 / k.max(1) as f64;
+    let p1 = (p0 + edge).min(1.0);
+    let games_needed = (k >= 2 && edge > 0.0).then(|| {
+        let n = (Z_95 * (p0 * (1.0 - p0)).sqrt() + Z_POWER_80 * (p1 * (1.0 - p1)).sqrt()) / edge;
+        (n * n).ceil() as u64
+    });
+    SeatAdvantage {seats: win_rates(winners, Z_95), chi_square, degrees, p_value, edge, games_needed}
+}
+
+/// Regularized upper incomplete gamma function Q(a, x), which gives chi-square tail probabilities
+fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    if x < a + 1.0 {
+        1.0 - gamma_p_series(a, x)
+    } else {
+        gamma_q_fraction(a, x)
+    }
+}
+
+/// P(a, x) by its series expansion, which converges quickly for x < a + 1
+fn gamma_p_series(a: f64, x: f64) -> f64 {
+    let (mut term, mut sum, mut n) = (1.0 / a, 1.0 / a, a);
+    for _ in 0..1000 {
+        n += 1.0;
+        term *= x / n;
+        sum += term;
+        if term.abs() < sum.abs() * 1e-15 {
+            break;
+        }
+    }
+    sum * (-x + a * x.ln() - ln_gamma(a)).exp()
+}
+
+/// Q(a, x) by its continued fraction (modified Lentz), which converges quickly for x >= a + 1
+fn gamma_q_fraction(a: f64, x: f64) -> f64 {
+    let tiny = 1e-300;
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / tiny;
+    let mut d = 1.0 / b;
+    let mut h = d;
+    for i in 1..1000 {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2.0;
+        d = an * d + b;
+        if d.abs() < tiny {
+            d = tiny;
+        }
+        c = b + an / c;
+        if c.abs() < tiny {
+            c = tiny;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+    (-x + a * x.ln() - ln_gamma(a)).exp() * h
+}
+
+/// ln Γ(x) for x > 0, by the Lanczos approximation
+fn ln_gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEF: [f64; 9] = [
+        0.999_999_999_999_809_9, 676.5203681218851, -1259.1392167224028, 771.323_428_777_653_1, -176.615_029_162_140_6,
+        12.507343278686905, -0.13857109526572012, 9.984_369_578_019_572e-6, 1.5056327351493116e-7,
+    ];
+    if x < 0.5 {
+        // Reflection formula
+        return (std::f64::consts::PI / (std::f64::consts::PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + G + 0.5;
+    let sum = COEF[1..].iter().enumerate().fold(COEF[0], |s, (i, c)| s + c / (x + i as f64 + 1.0));
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
@@ -0,0 +1,38 @@
+use crate::*;
+use std::io::{self, Write};
+
+/// Something that happened during a game
+/// Turns are numbered from 1 and count every player's turn, including skipped ones
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    Draw { turn: u32, player: u32, tile: Tile, num: u32 },
+    Move { turn: u32, player: u32, from: usize, to: usize },
+    Shortcut { turn: u32, player: u32, from: usize, to: usize },
+    Stuck { turn: u32, player: u32, space: usize }, // Landed on licorice, so the next turn is lost
+    Skip { turn: u32, player: u32, space: usize }, // Lost this turn to licorice
+    Reshuffle { turn: u32, count: u32 }, // The deck ran out for the `count`th time
+    Cycle { turn: u32, start: u32, length: u32 }, // The game is back in the state it was in on turn `start`
+    Win { turn: u32, player: u32 },
+}
+
+/// A turn-by-turn record of a single game
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Trace {
+    pub events: Vec<Event>,
+}
+
+impl Trace {
+    pub fn record(&mut self, e: Event) {
+        self.events.push(e);
+    }
+
+    /// Write the trace as JSON Lines, one event per line
+    pub fn write_jsonl<W: Write>(&self, mut w: W) -> io::Result<()> {
+        for e in &self.events {
+            serde_json::to_writer(&mut w, e)?;
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use candy_land::board::*;
+use candy_land::*;
+
+/// A small playable board with every picture once, and `extra` spaces just before the End
+fn board(extra: &str) -> String {
+    format!("start red icecreamcone gumdrop blue lollipop peppermint bonbon {} end", extra)
+}
+
+/// A board, a deck to check it against, and whether the error is the expected one
+type Case = (String, &'static str, fn(&BoardError) -> bool);
+
+fn check(board: &str, deck: &str) -> Result<(), BoardError> {
+    BoardSpec::parse_text(board)?.validate(&parse_deck(deck).unwrap())
+}
+
+#[test]
+fn valid_boards_pass() {
+    assert!(check(&board("red>9 blue*"), "rbIULEN").is_ok());
+    assert!(BoardSpec::default().validate(&make_deck()).is_ok());
+}
+
+#[test]
+fn invalid_boards_are_rejected() {
+    let cases: Vec<Case> = vec![
+        (board("start"), "rb", |e| matches!(e, BoardError::StartCount(2))),
+        (board("").replace("start", "red"), "rb", |e| matches!(e, BoardError::StartCount(0))),
+        (board("end"), "rb", |e| matches!(e, BoardError::EndCount(2))),
+        (board("").replace(" end", ""), "rb", |e| matches!(e, BoardError::EndCount(0))),
+        (board("").replace("start red", "red start"), "rb", |e| matches!(e, BoardError::Misplaced(Tile::Start))),
+        (board("").replace("end", "end blue"), "rb", |e| matches!(e, BoardError::Misplaced(Tile::End))),
+        (board("gumdrop"), "rb", |e| matches!(e, BoardError::SpecialCount {tile: Tile::Gumdrop, count: 2})),
+        (board("").replace("lollipop", "red"), "rb", |e| matches!(e, BoardError::SpecialCount {tile: Tile::Lollipop, count: 0})),
+        (board("red>3"), "rb", |e| matches!(e, BoardError::BadShortcut {from: 8, to: 3})),
+        (board("red>99"), "rb", |e| matches!(e, BoardError::BadShortcut {from: 8, to: 99})),
+        (board("").replace("start", "start>4"), "rb", |e| matches!(e, BoardError::BadShortcut {from: 0, to: 4})),
+        (board("").replace("start", "start*"), "rb", |e| matches!(e, BoardError::BadSticky(0))),
+        (board("").replace("end", "end*"), "rb", |e| matches!(e, BoardError::BadSticky(8))),
+        (board(""), "rbg", |e| matches!(e, BoardError::Unreachable(Card {tile: Tile::Green, num: 1}))),
+    ];
+    for (board, deck, expected) in cases {
+        match check(&board, deck) {
+            Err(e) => assert!(expected(&e), "{}: got {:?}", board, e),
+            Ok(()) => panic!("{}: accepted", board),
+        }
+    }
+}
+
+#[test]
+fn unreadable_boards_are_parse_errors() {
+    for board in ["start red purpel end", "start red>x end"] {
+        assert!(matches!(BoardSpec::parse_text(board), Err(BoardError::Parse(_))), "{}", board);
+    }
+}
+
+#[test]
+fn text_boards_round_trip() {
+    let board = BoardSpec::default();
+    assert_eq!(BoardSpec::parse_text(&board.to_text()).unwrap(), board);
+}
+
+#[test]
+fn configs_check_their_board_and_deck() {
+    let mut c = Config::new(PlayerCount::new(2).unwrap(), 1);
+    assert!(c.validate().is_ok());
+    c.board.shortcuts.push(Shortcut {from: 5, to: 500});
+    assert!(matches!(c.validate(), Err(ConfigError::Board(BoardError::BadShortcut {from: 5, to: 500}))));
+
+    let mut c = Config::new(PlayerCount::new(2).unwrap(), 1);
+    c.deck = Some(Vec::new());
+    assert!(matches!(c.validate(), Err(ConfigError::EmptyDeck)));
+
+    // A fixed deck is checked against the board, and the composition it doesn't use isn't
+    c.board = BoardSpec::parse_text(&board("")).unwrap();
+    c.deck = Some(parse_deck("rb").unwrap());
+    assert!(c.validate().is_ok());
+    c.deck = None;
+    assert!(matches!(c.validate(), Err(ConfigError::Composition(_))));
+}
@@ -0,0 +1,51 @@
+use candy_land::agents::AgentKind;
+use candy_land::board::BoardSpec;
+use candy_land::rules::Shortcuts;
+use candy_land::*;
+
+fn fixed_deck(deck: Vec<Card>) -> Config {
+    let mut c = Config::new(PlayerCount::new(2).unwrap(), 1);
+    c.deck = Some(deck);
+    c
+}
+
+#[test]
+fn infinite_deck_loops() {
+    let stats = replay(&fixed_deck(get_infinite_two_person_deck()), 0);
+    assert!(stats.infinite);
+    assert_eq!(stats.winner, 0);
+    assert_eq!(stats.cycle, Some(Cycle {start: 46, length: 90}));
+}
+
+#[test]
+fn finite_deck_finishes() {
+    let stats = replay(&fixed_deck(make_deck()), 0);
+    assert!(!stats.infinite);
+    assert!(stats.winner > 0);
+    assert_eq!(stats.cycle, None);
+}
+
+/// A board whose only shortcut is passed on every trip round a two card deck's loop
+fn shortcut_loop(agent: AgentKind) -> Config {
+    let mut c = fixed_deck(parse_deck("rI").unwrap());
+    c.players = PlayerCount::new(1).unwrap();
+    c.board = BoardSpec::parse_text("start icecreamcone red>3 blue green gumdrop lollipop peppermint bonbon end").unwrap();
+    c.rules.shortcuts = Shortcuts::Optional;
+    c.agents = vec![agent];
+    c
+}
+
+#[test]
+fn deterministic_choices_still_loop() {
+    let stats = replay(&shortcut_loop(AgentKind::Always), 0);
+    assert!(stats.infinite);
+    assert!(stats.cycle.is_some());
+}
+
+#[test]
+fn random_choices_stop_at_the_default_limit() {
+    let stats = replay(&shortcut_loop(AgentKind::Random), 0);
+    assert!(!stats.infinite);
+    assert!(stats.over_limit);
+    assert_eq!(stats.turns, RANDOM_CHOICE_MAX_TURNS);
+}
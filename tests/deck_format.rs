@@ -0,0 +1,27 @@
+use candy_land::*;
+
+#[test]
+fn written_decks_parse_back() {
+    for deck in [make_deck(), get_infinite_two_person_deck()] {
+        assert_eq!(parse_deck(&write_deck(&deck).unwrap()).unwrap(), deck);
+    }
+}
+
+#[test]
+fn invalid_cards_are_reported_at_their_character() {
+    // Whitespace is skipped but still counts towards the position
+    assert_eq!(parse_deck("rR\n  Bx"), Err(DeckParseError::InvalidCard {position: 6, found: 'x'}));
+    assert_eq!(parse_deck("bb\tr-"), Err(DeckParseError::InvalidCard {position: 4, found: '-'}));
+    assert_eq!(parse_deck(" \n"), Err(DeckParseError::Empty));
+}
+
+#[test]
+fn unwritable_cards_are_errors() {
+    let card = |tile, num| Card {tile, num};
+    for (bad, position) in [(card(Tile::Start, 1), 1), (card(Tile::End, 1), 0), (card(Tile::Red, 3), 2)] {
+        let mut deck = vec![card(Tile::Blue, 1), card(Tile::Green, 2)];
+        deck.insert(position, bad.clone());
+        let e = write_deck(&deck).unwrap_err();
+        assert_eq!((e.position, e.card), (position, bad));
+    }
+}
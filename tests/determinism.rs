@@ -0,0 +1,52 @@
+use candy_land::checkpoint::Checkpoint;
+use candy_land::*;
+
+/// A run of shuffled games, some of which go past the turn limit
+fn config(threads: usize) -> Config {
+    let mut c = Config::new(PlayerCount::new(3).unwrap(), 2000);
+    c.seed = 42;
+    c.threads = threads;
+    c.max_turns = Some(40);
+    c
+}
+
+/// A summary as JSON, without the wall-clock time that differs between runs
+fn results(s: &Summary) -> serde_json::Value {
+    let mut v = serde_json::to_value(s).unwrap();
+    v.as_object_mut().unwrap().remove("elapsed");
+    v
+}
+
+#[test]
+fn results_dont_depend_on_thread_count() {
+    let s = simulate(&config(1));
+    assert!(s.over_limit > 0);
+    for threads in [2, 3, 8] {
+        assert_eq!(results(&simulate(&config(threads))), results(&s), "{} threads", threads);
+    }
+}
+
+#[test]
+fn resumed_runs_match_uninterrupted_ones() {
+    let path = std::env::temp_dir().join(format!("candy_land_resume_{}.json", std::process::id()));
+
+    // Stop after the third batch of 150 games, as if the run had been killed
+    let mut cp = Checkpoint::new(&config(2));
+    let mut batches = 0;
+    let stopped = cp.run(150, |cp| {
+        cp.save(&path).unwrap();
+        batches += 1;
+        if batches == 3 { Err(()) } else { Ok(()) }
+    });
+    assert!(stopped.is_err());
+
+    // Pick it back up from the file on a different number of threads
+    let mut cp = Checkpoint::load(&path).unwrap();
+    assert_eq!(cp.completed, 450);
+    cp.config.threads = 5;
+    cp.run(150, |_| Ok::<(), ()>(())).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(cp.is_done());
+    assert_eq!(results(&cp.summary), results(&simulate(&config(1))));
+}
@@ -0,0 +1,40 @@
+use candy_land::board::BoardSpec;
+use candy_land::rules::*;
+use candy_land::trace::Event;
+use candy_land::*;
+
+/// The moves of a solitaire game on a short board ending in blue (#7), green (#8) and the End (#9)
+fn moves(deck: &str, end: EndRule) -> Vec<(usize, usize)> {
+    let mut c = Config::new(PlayerCount::new(1).unwrap(), 1);
+    c.board = BoardSpec::parse_text("start red icecreamcone gumdrop lollipop peppermint bonbon blue green end").unwrap();
+    c.deck = Some(parse_deck(deck).unwrap());
+    c.rules.end = end;
+    let (_, trace) = replay_traced(&c, 0);
+    trace.events.iter().filter_map(|e| match e {
+        Event::Move {from, to, ..} => Some((*from, *to)),
+        _ => None,
+    }).take(deck.len()).collect()
+}
+
+#[test]
+fn any_card_past_the_last_match_reaches_the_end() {
+    assert_eq!(moves("bb", EndRule::AnyCard), vec![(0, 7), (7, 9)]);
+}
+
+#[test]
+fn exact_color_loses_other_colors_near_the_end() {
+    // Blue isn't the last colored space's color, so it can't go on from #7; only green can
+    assert_eq!(moves("bbgg", EndRule::ExactColor), vec![(0, 7), (7, 7), (7, 8), (8, 9)]);
+}
+
+#[test]
+fn rainbow_loses_a_card_that_overshoots_the_end() {
+    // From #8 a double green would need two more greens, so it's lost; a single blue is one space to the End
+    assert_eq!(moves("bgGb", EndRule::Rainbow), vec![(0, 7), (7, 8), (8, 8), (8, 9)]);
+}
+
+#[test]
+fn rainbow_double_with_one_match_left_reaches_the_end() {
+    // From #7 a double green matches #8, and the End counts as the second green
+    assert_eq!(moves("bG", EndRule::Rainbow), vec![(0, 7), (7, 9)]);
+}
@@ -0,0 +1,29 @@
+use candy_land::board::BoardSpec;
+use candy_land::deck::DeckSpec;
+use candy_land::markov::*;
+use candy_land::rules::Rules;
+use candy_land::*;
+
+#[test]
+fn tiny_board_takes_exactly_three_turns() {
+    // One red card: onto the first red, the second, then past the last red to the End
+    let board = BoardSpec::parse_text("start red red icecreamcone gumdrop lollipop peppermint bonbon end").unwrap();
+    let deck = DeckSpec::from_deck(&[Card {tile: Tile::Red, num: 1}]);
+    let a = analyze(&board, &deck, &Rules::default()).unwrap();
+    assert_eq!(a.distribution, vec![0.0, 0.0, 0.0, 1.0]);
+    assert_eq!(a.mean, 3.0);
+    assert_eq!(a.variance, 0.0);
+}
+
+#[test]
+fn analysis_agrees_with_the_simulator() {
+    // The analysis draws with replacement, which a deck 10 times the usual size barely differs from
+    let a = analyze(&BoardSpec::default(), &DeckSpec::default(), &Rules::default()).unwrap();
+    let mut c = Config::new(PlayerCount::new(1).unwrap(), 20000);
+    c.seed = 7;
+    for e in &mut c.deck_spec.cards {
+        e.count *= 10;
+    }
+    let mean = simulate(&c).turns.mean();
+    assert!((mean - a.mean).abs() < a.mean * 0.02, "simulated {}, exact {}", mean, a.mean);
+}
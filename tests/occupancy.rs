@@ -0,0 +1,94 @@
+use candy_land::rules::*;
+use candy_land::trace::Event;
+use candy_land::*;
+
+/// The first few events of a game between `players` players drawing from a fixed deck
+fn events(deck: &str, players: u32, rules: Rules) -> Vec<Event> {
+    let mut c = Config::new(PlayerCount::new(players).unwrap(), 1);
+    c.deck = Some(parse_deck(deck).unwrap());
+    c.rules = rules;
+    let (_, trace) = replay_traced(&c, 0);
+    trace.events.into_iter().take(deck.len() * 3).collect()
+}
+
+fn moved(player: u32, from: usize, to: usize) -> impl Fn(&Event) -> bool {
+    move |e| matches!(e, Event::Move {player: p, from: f, to: t, ..} if (*p, *f, *t) == (player, from, to))
+}
+
+fn shortcut(player: u32) -> impl Fn(&Event) -> bool {
+    move |e| matches!(e, Event::Shortcut {player: p, ..} if *p == player)
+}
+
+#[test]
+fn color_moves_skip_taken_spaces() {
+    let e = events("rr", 2, Rules::default());
+    assert!(e.iter().any(moved(1, 0, 1)));
+    assert!(e.iter().any(moved(2, 0, 7)));
+}
+
+#[test]
+fn players_share_spaces_without_blocking() {
+    let e = events("rr", 2, Rules {blocking: false, ..Rules::default()});
+    assert!(e.iter().any(moved(2, 0, 1)));
+}
+
+#[test]
+fn shortcut_destination_is_taken() {
+    // Player 2 takes the shortcut from #4 to #36, so player 1's blue can't finish on #36
+    let e = events("Ubb", 2, Rules::default());
+    assert!(e.iter().any(shortcut(2)));
+    assert!(e.iter().any(moved(1, 33, 42)));
+}
+
+#[test]
+fn shortcut_origin_is_freed() {
+    // Player 1 takes the shortcut from #4 and then moves on, leaving it free for player 2
+    let e = events("brrb", 2, Rules::default());
+    assert!(e.iter().any(moved(1, 36, 39)));
+    assert!(e.iter().any(moved(2, 1, 4)));
+    assert!(e.iter().any(shortcut(2)));
+}
+
+#[test]
+fn mandatory_shortcut_to_taken_space_is_skipped() {
+    // Player 1 stands on #36, so landing on #4 would end there too
+    let e = events("Urbb", 2, Rules::default());
+    assert!(e.iter().any(moved(1, 33, 36)));
+    assert!(e.iter().any(moved(2, 1, 10)));
+}
+
+#[test]
+fn optional_shortcut_to_taken_space_cant_be_taken() {
+    let e = events("Urbb", 2, Rules {shortcuts: Shortcuts::Optional, ..Rules::default()});
+    assert!(e.iter().any(moved(2, 1, 4)));
+    assert!(!e.iter().any(shortcut(2)));
+}
+
+#[test]
+fn picture_spaces_are_shared() {
+    let e = events("UU", 2, Rules::default());
+    assert!(e.iter().any(moved(1, 0, 33)));
+    assert!(e.iter().any(moved(2, 0, 33)));
+}
+
+#[test]
+fn stuck_players_still_block() {
+    // Both players reach the Peppermint, then player 1 gets stuck on the licorice at #27
+    let e = events("EEpp", 2, Rules::default());
+    assert!(e.iter().any(moved(1, 21, 27)));
+    assert!(e.iter().any(|e| matches!(e, Event::Stuck {player: 1, space: 27, ..})));
+    assert!(e.iter().any(moved(2, 21, 34)));
+}
+
+#[test]
+fn licorice_until_color_holds_and_frees_the_space() {
+    // Both players share the Peppermint, then player 2 skips past player 1 held on the licorice at #27
+    // Player 1 sits out the Peppermint that sends player 2 back, then leaves on purple, freeing #27 for player 2
+    let e = events("EEppEEpp", 2, Rules {licorice: Licorice::UntilColor, ..Rules::default()});
+    assert!(e.iter().any(moved(2, 0, 21)));
+    assert!(e.iter().any(moved(2, 21, 34)));
+    assert!(e.iter().any(|e| matches!(e, Event::Skip {player: 1, space: 27, ..})));
+    assert!(e.iter().any(moved(2, 34, 21)));
+    assert!(e.iter().any(moved(1, 27, 34)));
+    assert!(e.iter().any(moved(2, 21, 27)));
+}
@@ -0,0 +1,83 @@
+use candy_land::stats::*;
+
+fn accumulator(turns: &[u32]) -> TurnAccumulator {
+    let mut t = TurnAccumulator::default();
+    for &n in turns {
+        t.add(n);
+    }
+    t
+}
+
+#[test]
+fn median_of_an_even_count_averages_the_middle_two() {
+    assert_eq!(accumulator(&[1, 2, 3, 4]).quantile(0.5), 2.5);
+    assert_eq!(accumulator(&[4, 1, 3, 2]).quantile(0.5), 2.5);
+}
+
+#[test]
+fn median_of_an_odd_count_is_the_middle_game() {
+    assert_eq!(accumulator(&[1, 2, 3]).quantile(0.5), 2.0);
+    assert_eq!(accumulator(&[3, 1, 2]).quantile(0.5), 2.0);
+}
+
+#[test]
+fn quantiles_interpolate_between_games() {
+    let t = accumulator(&[10, 20, 30, 40, 50]);
+    assert_eq!(t.quantile(0.0), 10.0);
+    assert_eq!(t.quantile(0.25), 20.0);
+    assert_eq!(t.quantile(0.9), 46.0);
+    assert_eq!(t.quantile(1.0), 50.0);
+}
+
+#[test]
+fn merged_accumulators_match_a_single_one() {
+    // Lengths on both sides of HISTOGRAM_LIMIT, so the histogram and the sketch are both merged
+    let turns: Vec<u32> = (0..3000).map(|i| 5 + (i * 7919) % 3000).collect();
+    let mut merged = TurnAccumulator::default();
+    for part in turns.chunks(700) {
+        merged.merge(&accumulator(part));
+    }
+    let whole = accumulator(&turns);
+    assert_eq!(serde_json::to_value(&merged).unwrap(), serde_json::to_value(&whole).unwrap());
+    for q in [0.05, 0.5, 0.95, 0.99] {
+        assert_eq!(merged.quantile(q), whole.quantile(q));
+    }
+}
+
+#[test]
+fn long_games_go_through_the_sketch() {
+    // 900 short games, then 100 taking 2000 to 2099 turns
+    let mut turns = vec![10; 900];
+    turns.extend(2000..2100);
+    let t = accumulator(&turns);
+    assert_eq!(t.quantile(0.5), 10.0);
+    let p95 = 2049.05;
+    assert!((t.quantile(0.95) - p95).abs() <= p95 * SKETCH_ACCURACY, "{}", t.quantile(0.95));
+    assert!(t.quantile(1.0) <= 2099.0);
+    assert_eq!(t.sketch.buckets.values().sum::<u64>(), 100);
+}
+
+#[test]
+fn chi_square_p_values_match_known_values() {
+    // 196 wins apart in 10000 games gives 3.8416 on 1 degree of freedom, the 5% critical value
+    let s = seat_advantage(&[5098, 4902], 0.01);
+    assert_eq!(s.degrees, 1);
+    assert!((s.chi_square - 3.8416).abs() < 1e-9);
+    assert!((s.p_value - 0.05).abs() < 1e-4, "{}", s.p_value);
+
+    // On 2 degrees of freedom the tail is exp(-chi_square / 2)
+    let s = seat_advantage(&[10, 20, 0], 0.01);
+    assert_eq!(s.degrees, 2);
+    assert!((s.chi_square - 20.0).abs() < 1e-9);
+    assert!((s.p_value / (-10.0f64).exp() - 1.0).abs() < 1e-6, "{}", s.p_value);
+
+    // Equal wins are no evidence at all
+    assert!((seat_advantage(&[50, 50, 50, 50], 0.01).p_value - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn solitaire_has_no_seat_advantage_to_detect() {
+    let s = seat_advantage(&[1000], 0.01);
+    assert_eq!(s.games_needed, None);
+    assert!(seat_advantage(&[500, 500], 0.01).games_needed.is_some());
+}